@@ -0,0 +1,140 @@
+use super::csv_field;
+use crate::attacks::templates::InjectionTemplate;
+use crate::attacks::{ObfuscationMode, ProfileConfig};
+use crate::pdf::{risk_score, scan_for_injections, DetectionFinding, PdfMutationRequest, PdfMutator, RealPdfMutator};
+use crate::{AnalysisError, Result};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+/// Scanner outcome for one profile and obfuscation mode.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct EvasionResult {
+    /// ID of the injected profile.
+    pub profile: String,
+    /// Obfuscation applied to the profile's phrases; always `None` for
+    /// profiles without text content.
+    pub obfuscation: ObfuscationMode,
+    /// ID of the generated variant.
+    pub variant_id: String,
+    /// Whether the scanner raised any finding the baseline did not.
+    pub detected: bool,
+    /// Highest confidence among those findings (0.0 when missed).
+    pub confidence: f32,
+    /// [`risk_score`] of those findings.
+    pub risk_score: f32,
+    /// Categories of those findings, see [`crate::pdf::FindingCategory::label`].
+    pub categories: Vec<String>,
+}
+
+/// Which injections the scanner caught and which it missed.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct EvasionReport {
+    /// Findings in a variant with no profiles applied (the base PDF plus the
+    /// mutator's own metadata marker). They are not credited to any injection.
+    pub baseline_findings: Vec<DetectionFinding>,
+    pub results: Vec<EvasionResult>,
+}
+
+impl EvasionReport {
+    /// Share of the results the scanner detected, or `None` without results.
+    pub fn detection_rate(&self) -> Option<f64> {
+        (!self.results.is_empty())
+            .then(|| self.results.iter().filter(|result| result.detected).count() as f64 / self.results.len() as f64)
+    }
+
+    /// Results the scanner missed.
+    pub fn evaded(&self) -> impl Iterator<Item = &EvasionResult> {
+        self.results.iter().filter(|result| !result.detected)
+    }
+
+    /// Writes the full report as pretty JSON.
+    pub fn write_json(&self, path: &Path) -> Result<()> {
+        let content = serde_json::to_string_pretty(self).map_err(|e| AnalysisError::JsonError(e.to_string()))?;
+        fs::write(path, content)?;
+        Ok(())
+    }
+
+    /// Writes one CSV row per profile and obfuscation mode.
+    pub fn write_csv(&self, path: &Path) -> Result<()> {
+        let mut out = String::from("profile,obfuscation,variant_id,detected,confidence,risk_score,categories\n");
+        for result in &self.results {
+            let row = [
+                result.profile.clone(),
+                format!("{:?}", result.obfuscation),
+                result.variant_id.clone(),
+                result.detected.to_string(),
+                result.confidence.to_string(),
+                result.risk_score.to_string(),
+                result.categories.join(";"),
+            ];
+            let row: Vec<String> = row.iter().map(|field| csv_field(field)).collect();
+            out.push_str(&row.join(","));
+            out.push('\n');
+        }
+        fs::write(path, out)?;
+        Ok(())
+    }
+}
+
+/// Injects every profile into `base_pdf` once per obfuscation mode, scans
+/// each variant with [`scan_for_injections`] and records whether it was
+/// caught. Variants are written to `output_dir`.
+///
+/// Findings already present in a variant with no profiles applied are
+/// ignored, so neither the base PDF nor the `CustomInjection` metadata
+/// marker count as a detection. Profiles without text content are run
+/// once, unobfuscated.
+pub fn evasion_report(
+    base_pdf: &Path,
+    output_dir: &Path,
+    profiles: &[ProfileConfig],
+    obfuscations: &[ObfuscationMode],
+    template: &InjectionTemplate,
+) -> Result<EvasionReport> {
+    let mutator = RealPdfMutator::new(output_dir);
+    let scan = |variant_id: String, profiles: Vec<ProfileConfig>| -> Result<(String, Vec<DetectionFinding>)> {
+        let mutation = mutator.mutate(PdfMutationRequest {
+            base_pdf: base_pdf.to_path_buf(),
+            profiles,
+            template: template.clone(),
+            variant_id: Some(variant_id),
+            ..Default::default()
+        })?;
+        Ok((mutation.variant_id, scan_for_injections(&mutation.mutated_pdf)?))
+    };
+
+    let (_, baseline_findings) = scan("evasion_baseline".to_string(), Vec::new())?;
+    let in_baseline = |finding: &DetectionFinding| {
+        baseline_findings.iter().any(|base| {
+            base.category == finding.category && base.page == finding.page && base.description == finding.description
+        })
+    };
+
+    let mut results = Vec::new();
+    for (index, profile) in profiles.iter().enumerate() {
+        let modes = if profile.content().is_some() { obfuscations } else { &[ObfuscationMode::None] };
+        for &obfuscation in modes {
+            let mut profile = profile.clone();
+            if let Some(content) = profile.content_mut() {
+                content.obfuscation = obfuscation;
+            }
+            let variant_id =
+                format!("evasion_{}_{}_{:?}", index, profile.id().trim_start_matches("pdf."), obfuscation).to_lowercase();
+            let profile_id = profile.id().to_string();
+            let (variant_id, findings) = scan(variant_id, vec![profile])?;
+            let findings: Vec<DetectionFinding> = findings.into_iter().filter(|finding| !in_baseline(finding)).collect();
+            results.push(EvasionResult {
+                profile: profile_id,
+                obfuscation,
+                variant_id,
+                detected: !findings.is_empty(),
+                confidence: findings.iter().map(|finding| finding.confidence).fold(0.0, f32::max),
+                risk_score: risk_score(&findings),
+                categories: findings.iter().map(|finding| finding.category.label().to_string()).collect(),
+            });
+        }
+    }
+
+    Ok(EvasionReport { baseline_findings, results })
+}
@@ -0,0 +1,177 @@
+use super::{ScenarioReport, VariantImpact};
+use std::fmt::Write;
+
+/// Height of one variant's group of bars in the SVG chart.
+const CHART_ROW_HEIGHT: f64 = 36.0;
+/// Width of the label column left of the bars.
+const CHART_LABEL_WIDTH: f64 = 260.0;
+/// Width the largest score is scaled to.
+const CHART_BAR_WIDTH: f64 = 400.0;
+
+const STYLE: &str = "body{font-family:sans-serif;background:#0a0a0a;color:#e0e0e0;margin:2em}\
+h1{color:#ff4500}table{border-collapse:collapse;margin:1em 0}\
+th,td{border:1px solid #444;padding:4px 8px;text-align:left}th{background:#1e1e1e}\
+details{margin:.5em 0;padding:.5em;border:1px solid #333}summary{cursor:pointer;font-weight:bold}\
+pre{white-space:pre-wrap;background:#1e1e1e;padding:.5em}.before{fill:#888}.after{fill:#ff4500}";
+
+impl ScenarioReport {
+    /// Renders the report as a self-contained HTML page: a variant table, an
+    /// inline SVG chart of score before/after and collapsible per-variant
+    /// details. Uses no external scripts or stylesheets.
+    pub fn to_html(&self) -> String {
+        let mut html = String::new();
+        let title = format!("Scenario report: {}", self.scenario_id);
+        let _ = write!(
+            html,
+            "<!DOCTYPE html>\n<html><head><meta charset=\"utf-8\"><title>{}</title><style>{}</style></head><body>\n",
+            escape(&title),
+            STYLE
+        );
+        let _ = writeln!(html, "<h1>{}</h1>", escape(&title));
+        if let Some(target) = &self.target {
+            let _ = writeln!(html, "<p>Target: {}</p>", escape(target));
+        }
+        for note in &self.notes {
+            let _ = writeln!(html, "<p><em>{}</em></p>", escape(note));
+        }
+
+        html.push_str("<table><tr><th>Variant</th><th>Profiles</th><th>Score before</th><th>Score after</th><th>Class before</th><th>Class after</th></tr>\n");
+        for variant in &self.variants {
+            let _ = writeln!(
+                html,
+                "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>",
+                escape(&variant.variant_id),
+                escape(&variant.profiles.join(", ")),
+                score_cell(variant.score_before),
+                score_cell(variant.score_after),
+                escape(variant.classification_before.as_deref().unwrap_or("-")),
+                escape(variant.classification_after.as_deref().unwrap_or("-")),
+            );
+        }
+        html.push_str("</table>\n");
+
+        html.push_str("<h2>Scores</h2>\n");
+        html.push_str(&score_chart(&self.variants));
+
+        html.push_str("<h2>Details</h2>\n");
+        for variant in &self.variants {
+            let _ = writeln!(html, "<details><summary>{}</summary>", escape(&variant.variant_id));
+            let _ = writeln!(html, "<p>Profiles: {}</p>", escape(&variant.profiles.join(", ")));
+            let _ = writeln!(html, "<p>Templates: {}</p>", escape(&variant.templates.join(", ")));
+            if let Some(hash) = &variant.variant_hash {
+                let _ = writeln!(html, "<p>Hash: <code>{}</code></p>", escape(hash));
+            }
+            if !variant.notes.is_empty() {
+                html.push_str("<ul>");
+                for note in &variant.notes {
+                    let _ = write!(html, "<li>{}</li>", escape(note));
+                }
+                html.push_str("</ul>\n");
+            }
+            if let Some(sample) = &variant.llm_response_sample {
+                let _ = writeln!(html, "<p>Response sample:</p><pre>{}</pre>", escape(sample));
+            }
+            html.push_str("</details>\n");
+        }
+
+        html.push_str("</body></html>\n");
+        html
+    }
+}
+
+/// Draws a grey (before) and an accent (after) bar per variant.
+fn score_chart(variants: &[VariantImpact]) -> String {
+    let max_score = variants
+        .iter()
+        .flat_map(|v| [v.score_before, v.score_after])
+        .flatten()
+        .fold(0.0_f64, f64::max);
+    let scale = if max_score > 0.0 { CHART_BAR_WIDTH / max_score } else { 0.0 };
+    let width = CHART_LABEL_WIDTH + CHART_BAR_WIDTH + 60.0;
+    let height = CHART_ROW_HEIGHT * variants.len() as f64 + 10.0;
+
+    let mut svg = String::new();
+    let _ = writeln!(
+        svg,
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\" font-size=\"12\">",
+        width, height
+    );
+    for (index, variant) in variants.iter().enumerate() {
+        let y = index as f64 * CHART_ROW_HEIGHT + 5.0;
+        let _ = writeln!(
+            svg,
+            "<text x=\"0\" y=\"{:.1}\" fill=\"#e0e0e0\">{}</text>",
+            y + 18.0,
+            escape(&variant.variant_id)
+        );
+        for (offset, class, score) in [(0.0, "before", variant.score_before), (14.0, "after", variant.score_after)] {
+            let Some(score) = score else { continue };
+            let bar = (score.max(0.0) * scale).max(1.0);
+            let _ = writeln!(
+                svg,
+                "<rect class=\"{}\" x=\"{}\" y=\"{:.1}\" width=\"{:.1}\" height=\"12\"><title>{} {}</title></rect>\
+                 <text x=\"{:.1}\" y=\"{:.1}\" fill=\"#e0e0e0\">{}</text>",
+                class,
+                CHART_LABEL_WIDTH,
+                y + offset,
+                bar,
+                class,
+                score,
+                CHART_LABEL_WIDTH + bar + 4.0,
+                y + offset + 10.0,
+                score
+            );
+        }
+    }
+    svg.push_str("</svg>\n");
+    svg
+}
+
+fn score_cell(score: Option<f64>) -> String {
+    score.map(|s| s.to_string()).unwrap_or_else(|| "-".to_string())
+}
+
+/// Escapes text for use in HTML element content and attribute values.
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_html_escapes_and_charts_scores() {
+        let report = ScenarioReport {
+            scenario_id: "html<test>".into(),
+            target: None,
+            variants: vec![VariantImpact {
+                variant_id: "v1".into(),
+                score_before: Some(20.0),
+                score_after: Some(40.0),
+                classification_before: None,
+                classification_after: None,
+                llm_response_sample: Some("<script>alert(1)</script>".into()),
+                profiles: vec!["pdf.visible_meta_block".into()],
+                templates: vec!["soft_bias".into()],
+                mutated_pdf: None,
+                variant_hash: None,
+                notes: vec![],
+                text_diff: None,
+                phrase_positions: Vec::new(),
+            }],
+            notes: vec![],
+        };
+
+        let html = report.to_html();
+        assert!(html.contains("html&lt;test&gt;"));
+        assert!(!html.contains("<script>"));
+        assert!(html.contains("<svg"));
+        assert_eq!(html.matches("<rect").count(), 2);
+        assert!(html.contains("<details>"));
+    }
+}
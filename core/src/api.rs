@@ -0,0 +1,66 @@
+use crate::attacks::templates::{default_templates, InjectionTemplate};
+use crate::attacks::ProfileConfig;
+use crate::docx::{self, DocxMutator};
+use crate::pdf::{DocumentMutator, PdfMutationRequest, PdfMutationResult, RealPdfMutator};
+use crate::Result;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// The `default` built-in template, or the first one if it is missing.
+pub fn default_template() -> InjectionTemplate {
+    let mut templates = default_templates();
+    match templates.iter().position(|t| t.id == "default") {
+        Some(index) => templates.swap_remove(index),
+        None => templates.swap_remove(0),
+    }
+}
+
+/// Applies `profiles` to `input` (a PDF or DOCX) and writes the result to
+/// `output`. The variant ID is taken from `output`'s file stem.
+pub fn inject_pdf(
+    input: &Path,
+    output: &Path,
+    profiles: Vec<ProfileConfig>,
+    template: InjectionTemplate,
+) -> Result<PdfMutationResult> {
+    let request = PdfMutationRequest {
+        base_pdf: input.to_path_buf(),
+        profiles,
+        template,
+        ..Default::default()
+    };
+    inject_request(request, output)
+}
+
+/// Like [`inject_pdf`], for callers that need the rest of the request (seed,
+/// passwords, incremental updates...). A missing `variant_id` defaults to
+/// `output`'s file stem.
+///
+/// The mutator writes into a uniquely named scratch directory next to
+/// `output`, and the result is then renamed to `output`; the returned
+/// `mutated_pdf` points at `output`. A `variant_id` that matches another file
+/// in `output`'s directory, including the input, never overwrites it.
+pub fn inject_request(mut request: PdfMutationRequest, output: &Path) -> Result<PdfMutationResult> {
+    let output_dir = match output.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir.to_path_buf(),
+        _ => PathBuf::from("."),
+    };
+    if request.variant_id.is_none() {
+        request.variant_id = output.file_stem().map(|stem| stem.to_string_lossy().into_owned());
+    }
+
+    let scratch_dir = output_dir.join(format!(".superpoweredcv-{}", uuid::Uuid::new_v4()));
+    fs::create_dir(&scratch_dir)?;
+    let mutator: Box<dyn DocumentMutator> = if docx::is_docx(&request.base_pdf) {
+        Box::new(DocxMutator::new(&scratch_dir))
+    } else {
+        Box::new(RealPdfMutator::new(&scratch_dir))
+    };
+    let outcome = mutator.mutate(request).and_then(|mut result| {
+        fs::rename(&result.mutated_pdf, output)?;
+        result.mutated_pdf = output.to_path_buf();
+        Ok(result)
+    });
+    let _ = fs::remove_dir_all(&scratch_dir);
+    outcome
+}
@@ -27,7 +27,7 @@ pub struct AtsSimulator {
 impl AtsSimulator {
     pub fn new(config: &AppConfig) -> Self {
         Self {
-            llm_client: LlmClient::new(config.llm.clone()),
+            llm_client: LlmClient::with_network(config.llm.clone(), &config.network),
         }
     }
 
@@ -1,3 +1,4 @@
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 /// Severity level of the injection template.
@@ -34,7 +35,7 @@ pub enum ControlType {
 }
 
 /// Type of content generation.
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
 pub enum GenerationType {
     /// Static text from template.
     Static,
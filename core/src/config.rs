@@ -1,5 +1,41 @@
 use serde::{Deserialize, Serialize};
 use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// Default location of the configuration file.
+pub const CONFIG_PATH: &str = "config.json";
+
+/// Keyring service holding LLM API keys, one entry per provider.
+pub const KEYRING_SERVICE: &str = "superpoweredcv";
+
+/// Environment variable that overrides every other LLM API key source.
+pub const API_KEY_ENV: &str = "SUPERPOWEREDCV_API_KEY";
+
+/// Where [`LlmConfig::resolve_api_key`] found the key. Never carries the key itself.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ApiKeySource {
+    /// An environment variable, by name.
+    Env(&'static str),
+    /// The OS keyring entry for the provider.
+    Keyring,
+    /// `llm.api_key` in the config file.
+    Config,
+    /// No key is configured.
+    None,
+}
+
+impl std::fmt::Display for ApiKeySource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ApiKeySource::Env(var) => write!(f, "environment variable {}", var),
+            ApiKeySource::Keyring => write!(f, "OS keyring"),
+            ApiKeySource::Config => write!(f, "config file"),
+            ApiKeySource::None => write!(f, "none"),
+        }
+    }
+}
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AppConfig {
@@ -7,6 +43,13 @@ pub struct AppConfig {
     pub prompts: PromptConfig,
     pub latex: LatexConfig,
     pub history: HistoryConfig,
+    #[serde(default)]
+    pub theme: ThemeConfig,
+    #[serde(default)]
+    pub network: NetworkConfig,
+    /// Modification time of the file when it was loaded or last saved.
+    #[serde(skip)]
+    loaded_mtime: Option<SystemTime>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -14,6 +57,160 @@ pub struct LlmConfig {
     pub api_base_url: String,
     pub model: String,
     pub api_key: Option<String>,
+    /// The key lives in the OS keyring under [`LlmConfig::provider_name`]
+    /// and `api_key` is never written to disk.
+    #[serde(default)]
+    pub api_key_in_keyring: bool,
+    #[serde(default = "default_temperature")]
+    pub temperature: f32,
+    #[serde(default = "default_max_tokens")]
+    pub max_tokens: u32,
+    /// Sent as a leading system message when non-empty.
+    #[serde(default)]
+    pub system_prompt: String,
+    #[serde(default)]
+    pub api: LlmApi,
+    /// Reuse responses stored under [`response_cache_dir`](Self::response_cache_dir)
+    /// for identical requests instead of calling the provider again.
+    #[serde(default)]
+    pub cache_enabled: bool,
+    /// Where cached responses live; [`crate::llm::default_cache_dir`] when unset.
+    #[serde(default)]
+    pub cache_dir: Option<PathBuf>,
+}
+
+/// Request schema spoken by the configured endpoint.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+pub enum LlmApi {
+    /// Picks Anthropic for `anthropic.com` URLs, Gemini for Google's
+    /// `generativelanguage` URLs outside the `/openai` shim, and
+    /// OpenAI-compatible otherwise.
+    #[default]
+    Auto,
+    /// `/chat/completions` with bearer authentication.
+    OpenAiCompatible,
+    /// Anthropic's native `/messages` API.
+    Anthropic,
+    /// Google's native `models/{model}:generateContent` API, keyed by an
+    /// `x-goog-api-key` header.
+    Gemini,
+}
+
+impl LlmConfig {
+    /// The configured `cache_dir`, else the per-user default.
+    pub fn response_cache_dir(&self) -> PathBuf {
+        self.cache_dir.clone().unwrap_or_else(crate::llm::default_cache_dir)
+    }
+
+    /// Resolves [`LlmApi::Auto`] against the base URL.
+    pub fn resolved_api(&self) -> LlmApi {
+        match self.api {
+            LlmApi::Auto if self.api_base_url.contains("anthropic.com") => LlmApi::Anthropic,
+            LlmApi::Auto
+                if self.api_base_url.contains("generativelanguage.googleapis.com")
+                    && !self.api_base_url.trim_end_matches('/').ends_with("/openai") =>
+            {
+                LlmApi::Gemini
+            }
+            LlmApi::Auto => LlmApi::OpenAiCompatible,
+            api => api,
+        }
+    }
+
+    /// Keyring user name for this provider: the host of `api_base_url`, or
+    /// the whole URL when it has no recognizable host.
+    pub fn provider_name(&self) -> String {
+        let url = self.api_base_url.trim();
+        let rest = url.split_once("://").map_or(url, |(_, rest)| rest);
+        match rest.split(['/', ':']).next() {
+            Some(host) if !host.is_empty() => host.to_string(),
+            _ => url.to_string(),
+        }
+    }
+
+    fn keyring_entry(&self) -> Result<keyring::Entry, String> {
+        keyring::Entry::new(KEYRING_SERVICE, &self.provider_name()).map_err(|e| e.to_string())
+    }
+
+    /// Moves `api_key` into the OS keyring and sets `api_key_in_keyring`.
+    pub fn store_api_key_in_keyring(&mut self) -> Result<(), String> {
+        let key = self.api_key.as_deref().filter(|key| !key.is_empty()).ok_or("no API key to store")?;
+        self.keyring_entry()?.set_password(key).map_err(|e| e.to_string())?;
+        self.api_key_in_keyring = true;
+        self.api_key = None;
+        Ok(())
+    }
+
+    /// The key stored in the keyring for this provider, if any.
+    pub fn keyring_api_key(&self) -> Option<String> {
+        self.keyring_entry().ok()?.get_password().ok()
+    }
+
+    /// Deletes this provider's keyring entry and clears `api_key_in_keyring`.
+    pub fn clear_keyring_api_key(&mut self) -> Result<(), String> {
+        match self.keyring_entry()?.delete_credential() {
+            Ok(()) | Err(keyring::Error::NoEntry) => {
+                self.api_key_in_keyring = false;
+                Ok(())
+            }
+            Err(e) => Err(e.to_string()),
+        }
+    }
+
+    /// Provider-specific key variable for well-known hosted APIs, e.g.
+    /// `OPENAI_API_KEY` for `api.openai.com`.
+    pub fn provider_env_var(&self) -> Option<&'static str> {
+        let host = self.provider_name();
+        [
+            ("openai.com", "OPENAI_API_KEY"),
+            ("anthropic.com", "ANTHROPIC_API_KEY"),
+            ("mistral.ai", "MISTRAL_API_KEY"),
+            ("groq.com", "GROQ_API_KEY"),
+            ("openrouter.ai", "OPENROUTER_API_KEY"),
+            ("googleapis.com", "GEMINI_API_KEY"),
+            ("cohere.ai", "COHERE_API_KEY"),
+            ("deepseek.com", "DEEPSEEK_API_KEY"),
+        ]
+        .into_iter()
+        .find(|(domain, _)| host == *domain || host.ends_with(&format!(".{}", domain)))
+        .map(|(_, var)| var)
+    }
+
+    /// The key to authenticate with, in order of precedence:
+    ///
+    /// 1. `SUPERPOWEREDCV_API_KEY`
+    /// 2. the provider's own variable (see [`provider_env_var`](Self::provider_env_var))
+    /// 3. the plaintext `api_key`, which while `api_key_in_keyring` is set
+    ///    only holds a newly entered key that has not been saved yet
+    /// 4. the OS keyring, when `api_key_in_keyring` is set
+    ///
+    /// Empty environment variables are ignored.
+    pub fn resolve_api_key(&self) -> (Option<String>, ApiKeySource) {
+        self.resolve_api_key_with(|var| std::env::var(var).ok())
+    }
+
+    fn resolve_api_key_with(&self, env: impl Fn(&str) -> Option<String>) -> (Option<String>, ApiKeySource) {
+        for var in std::iter::once(API_KEY_ENV).chain(self.provider_env_var()) {
+            if let Some(key) = env(var).filter(|key| !key.is_empty()) {
+                return (Some(key), ApiKeySource::Env(var));
+            }
+        }
+        if let Some(key) = &self.api_key {
+            return (Some(key.clone()), ApiKeySource::Config);
+        }
+        match self.api_key_in_keyring.then(|| self.keyring_api_key()).flatten() {
+            Some(key) => (Some(key), ApiKeySource::Keyring),
+            None => (None, ApiKeySource::None),
+        }
+    }
+}
+
+fn default_temperature() -> f32 {
+    0.7
+}
+
+fn default_max_tokens() -> u32 {
+    512
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -27,12 +224,85 @@ pub struct PromptConfig {
 pub struct LatexConfig {
     pub binary_path: String,
     pub auto_detect: bool,
+    #[serde(default)]
+    pub engine: LatexEngine,
+}
+
+/// TeX engine used to compile exported resumes.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+pub enum LatexEngine {
+    #[default]
+    Pdflatex,
+    Xelatex,
+    /// Self-contained engine that fetches packages on demand.
+    Tectonic,
+}
+
+impl LatexEngine {
+    pub const ALL: [LatexEngine; 3] = [LatexEngine::Pdflatex, LatexEngine::Xelatex, LatexEngine::Tectonic];
+
+    /// Binary name looked up on the PATH for this engine.
+    pub fn default_binary(&self) -> &'static str {
+        match self {
+            LatexEngine::Pdflatex => "pdflatex",
+            LatexEngine::Xelatex => "xelatex",
+            LatexEngine::Tectonic => "tectonic",
+        }
+    }
+
+    /// Recognizes an engine from a binary name or path such as `/usr/bin/xelatex`.
+    pub fn from_binary(binary: &str) -> Option<Self> {
+        let stem = Path::new(binary).file_stem()?.to_str()?.to_lowercase();
+        Self::ALL.into_iter().find(|engine| engine.default_binary() == stem)
+    }
+}
+
+impl LatexConfig {
+    /// Binary to run for the selected engine: the configured path, or the
+    /// engine's default name when the path is empty.
+    pub fn resolved_binary(&self) -> &str {
+        match self.binary_path.trim() {
+            "" => self.engine.default_binary(),
+            path => path,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct HistoryConfig {
     pub recent_json_files: Vec<String>,
     pub max_history_size: usize,
+    /// Injection module presets saved or loaded from the GUI, most recent first.
+    #[serde(default)]
+    pub recent_presets: Vec<String>,
+}
+
+/// Settings shared by every outgoing HTTP client (LLM and ATS endpoints).
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct NetworkConfig {
+    /// Proxy URL for all requests, e.g. `http://proxy.corp:8080`. When unset,
+    /// the `HTTPS_PROXY`/`HTTP_PROXY`/`NO_PROXY` environment variables apply.
+    #[serde(default)]
+    pub proxy: Option<String>,
+}
+
+impl NetworkConfig {
+    /// Builds a blocking client with the configured proxy. `NO_PROXY` is
+    /// still honored for an explicit proxy.
+    pub fn client_builder(&self) -> reqwest::Result<reqwest::blocking::ClientBuilder> {
+        let builder = reqwest::blocking::Client::builder();
+        match self.proxy.as_deref().map(str::trim).filter(|proxy| !proxy.is_empty()) {
+            Some(proxy) => Ok(builder.proxy(reqwest::Proxy::all(proxy)?.no_proxy(reqwest::NoProxy::from_env()))),
+            None => Ok(builder),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ThemeConfig {
+    pub accent_rgb: [u8; 3],
+    pub background_rgb: [u8; 3],
+    pub corner_radius: u8,
 }
 
 impl Default for AppConfig {
@@ -42,6 +312,9 @@ impl Default for AppConfig {
             prompts: PromptConfig::default(),
             latex: LatexConfig::default(),
             history: HistoryConfig::default(),
+            theme: ThemeConfig::default(),
+            network: NetworkConfig::default(),
+            loaded_mtime: None,
         }
     }
 }
@@ -52,6 +325,13 @@ impl Default for LlmConfig {
             api_base_url: "http://localhost:1234/v1".to_string(), // Default to local LM Studio/Ollama
             model: "local-model".to_string(),
             api_key: None,
+            api_key_in_keyring: false,
+            temperature: default_temperature(),
+            max_tokens: default_max_tokens(),
+            system_prompt: String::new(),
+            api: LlmApi::Auto,
+            cache_enabled: false,
+            cache_dir: None,
         }
     }
 }
@@ -71,6 +351,7 @@ impl Default for LatexConfig {
         Self {
             binary_path: "pdflatex".to_string(),
             auto_detect: true,
+            engine: LatexEngine::Pdflatex,
         }
     }
 }
@@ -80,37 +361,214 @@ impl Default for HistoryConfig {
         Self {
             recent_json_files: Vec::new(),
             max_history_size: 5,
+            recent_presets: Vec::new(),
+        }
+    }
+}
+
+impl Default for ThemeConfig {
+    fn default() -> Self {
+        Self {
+            accent_rgb: [255, 69, 0], // Red-Orange (Fireish)
+            background_rgb: [10, 10, 10],
+            corner_radius: 0,
         }
     }
 }
 
 impl AppConfig {
     pub fn load() -> Self {
-        // In a real app, we'd load from a file. For now, return defaults or try to load from a local config.json
-        if let Ok(content) = fs::read_to_string("config.json") {
-            if let Ok(config) = serde_json::from_str(&content) {
+        Self::load_from(Path::new(CONFIG_PATH))
+    }
+
+    pub fn load_from(path: &Path) -> Self {
+        if let Ok(content) = fs::read_to_string(path) {
+            if let Ok(mut config) = serde_json::from_str::<Self>(&content) {
+                config.loaded_mtime = modified_time(path);
                 return config;
             }
         }
         Self::default()
     }
 
-    pub fn save(&self) -> std::io::Result<()> {
-        let content = serde_json::to_string_pretty(self)?;
-        fs::write("config.json", content)
+    pub fn save(&mut self) -> io::Result<()> {
+        self.save_to(Path::new(CONFIG_PATH))
     }
 
-    pub fn add_recent_file(&mut self, path: &str) {
-        // Remove if exists to move to top
-        if let Some(pos) = self.history.recent_json_files.iter().position(|x| x == path) {
-            self.history.recent_json_files.remove(pos);
+    /// Writes the config atomically: the JSON goes to a temp file in the same
+    /// directory which is then renamed over the target, so a failed write never
+    /// leaves a truncated config behind.
+    pub fn save_to(&mut self, path: &Path) -> io::Result<()> {
+        // A key entered after the keyring was enabled replaces the stored one.
+        if self.llm.api_key_in_keyring && self.llm.api_key.as_deref().is_some_and(|key| !key.is_empty()) {
+            self.llm.store_api_key_in_keyring().map_err(io::Error::other)?;
         }
-        self.history.recent_json_files.insert(0, path.to_string());
-        if self.history.recent_json_files.len() > self.history.max_history_size {
-            self.history.recent_json_files.truncate(self.history.max_history_size);
+        let content = if self.llm.api_key_in_keyring && self.llm.api_key.is_some() {
+            let mut redacted = self.clone();
+            redacted.llm.api_key = None;
+            serde_json::to_string_pretty(&redacted)?
+        } else {
+            serde_json::to_string_pretty(self)?
+        };
+        replace_via(&temp_path_for(path), path, &content)?;
+        self.loaded_mtime = modified_time(path);
+        Ok(())
+    }
+
+    /// Returns true if the config file changed on disk since it was loaded or saved.
+    pub fn modified_externally(&self) -> bool {
+        self.modified_externally_at(Path::new(CONFIG_PATH))
+    }
+
+    pub fn modified_externally_at(&self, path: &Path) -> bool {
+        match (self.loaded_mtime, modified_time(path)) {
+            (Some(loaded), Some(current)) => current != loaded,
+            (None, Some(_)) => true,
+            _ => false,
         }
+    }
+
+    pub fn add_recent_file(&mut self, path: &str) {
+        push_recent(&mut self.history.recent_json_files, path, self.history.max_history_size);
+        let _ = self.save();
+    }
+
+    pub fn add_recent_preset(&mut self, path: &str) {
+        push_recent(&mut self.history.recent_presets, path, self.history.max_history_size);
         let _ = self.save();
     }
 }
 
+fn push_recent(list: &mut Vec<String>, path: &str, max: usize) {
+    // Remove if exists to move to top
+    if let Some(pos) = list.iter().position(|x| x == path) {
+        list.remove(pos);
+    }
+    list.insert(0, path.to_string());
+    if list.len() > max {
+        list.truncate(max);
+    }
+}
+
+fn modified_time(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+/// A temp file next to `path`, unique per call so concurrent savers (say the
+/// GUI and the CLI) never write to or rename each other's file.
+fn temp_path_for(path: &Path) -> PathBuf {
+    let name = path.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+    path.with_file_name(format!(".{}.{}.tmp", name, uuid::Uuid::new_v4()))
+}
+
+/// Writes `content` to `temp_path` and renames it over `path`, removing the
+/// temp file if either step fails.
+fn replace_via(temp_path: &Path, path: &Path, content: &str) -> io::Result<()> {
+    fs::write(temp_path, content).and_then(|_| fs::rename(temp_path, path)).inspect_err(|_| {
+        let _ = fs::remove_file(temp_path);
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_failed_save_keeps_previous_config() {
+        let dir = std::env::temp_dir().join(format!("superpoweredcv_config_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("config.json");
+
+        let mut config = AppConfig::default();
+        config.llm.model = "first-model".to_string();
+        config.save_to(&path).unwrap();
+        config.save_to(&path).unwrap();
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 1, "temp files left behind");
+        assert_ne!(temp_path_for(&path), temp_path_for(&path));
+
+        // Block the temp file so the next write fails before the rename.
+        let blocked = temp_path_for(&path);
+        fs::create_dir_all(&blocked).unwrap();
+        config.llm.model = "second-model".to_string();
+        let content = serde_json::to_string_pretty(&config).unwrap();
+        assert!(replace_via(&blocked, &path, &content).is_err());
+
+        let survived = AppConfig::load_from(&path);
+        assert_eq!(survived.llm.model, "first-model");
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
 
+    #[test]
+    fn test_keyring_flag_keeps_api_key_off_disk() {
+        let dir = std::env::temp_dir().join(format!("superpoweredcv_keyring_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("config.json");
+
+        keyring::set_default_credential_builder(keyring::mock::default_credential_builder());
+        let mut config = AppConfig::default();
+        config.llm.api_base_url = "https://api.openai.com/v1".to_string();
+        config.llm.api_key = Some("sk-secret".to_string());
+        config.llm.api_key_in_keyring = true;
+        config.save_to(&path).unwrap();
+
+        // The edited key went to the keyring rather than being dropped.
+        assert!(!fs::read_to_string(&path).unwrap().contains("sk-secret"));
+        assert_eq!(config.llm.api_key, None);
+        assert!(config.llm.api_key_in_keyring);
+        assert_eq!(config.llm.provider_name(), "api.openai.com");
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_api_key_precedence() {
+        let mut llm = LlmConfig {
+            api_base_url: "https://api.openai.com/v1".to_string(),
+            api_key: Some("from-config".to_string()),
+            ..LlmConfig::default()
+        };
+        let env = |vars: &'static [(&'static str, &'static str)]| {
+            move |name: &str| vars.iter().find(|(var, _)| *var == name).map(|(_, value)| value.to_string())
+        };
+
+        assert_eq!(
+            llm.resolve_api_key_with(env(&[(API_KEY_ENV, "global"), ("OPENAI_API_KEY", "openai")])),
+            (Some("global".to_string()), ApiKeySource::Env(API_KEY_ENV))
+        );
+        assert_eq!(
+            llm.resolve_api_key_with(env(&[(API_KEY_ENV, ""), ("OPENAI_API_KEY", "openai")])),
+            (Some("openai".to_string()), ApiKeySource::Env("OPENAI_API_KEY"))
+        );
+        assert_eq!(llm.resolve_api_key_with(env(&[("ANTHROPIC_API_KEY", "other")])).1, ApiKeySource::Config);
+
+        // An unsaved key entered while the keyring is in use wins over the stored one.
+        llm.api_key_in_keyring = true;
+        assert_eq!(llm.resolve_api_key_with(env(&[])), (Some("from-config".to_string()), ApiKeySource::Config));
+        llm.api_key_in_keyring = false;
+
+        llm.api_key = None;
+        assert_eq!(llm.resolve_api_key_with(env(&[])), (None, ApiKeySource::None));
+        llm.api_base_url = "http://localhost:1234/v1".to_string();
+        assert_eq!(llm.provider_env_var(), None);
+    }
+
+    #[test]
+    fn test_network_proxy_validation() {
+        let mut network = NetworkConfig::default();
+        assert!(network.client_builder().is_ok());
+        network.proxy = Some("http://proxy.example:8080".to_string());
+        assert!(network.client_builder().and_then(|b| b.build()).is_ok());
+        network.proxy = Some("not a url".to_string());
+        assert!(network.client_builder().is_err());
+    }
+
+    #[test]
+    fn test_legacy_llm_config_gets_sampling_defaults() {
+        let llm: LlmConfig = serde_json::from_str(r#"{"api_base_url":"http://localhost:1234/v1","model":"m","api_key":null}"#).unwrap();
+        assert_eq!(llm.temperature, 0.7);
+        assert_eq!(llm.max_tokens, 512);
+        assert!(llm.system_prompt.is_empty());
+        assert!(!llm.api_key_in_keyring);
+    }
+}
@@ -0,0 +1,371 @@
+use crate::attacks::{InjectionPosition, ProfileConfig, StructuralTarget};
+use crate::pdf::{fragments_text, get_injection_text, repeats_per_page, request_seed, DocumentMutator, PdfMutationRequest, PdfMutationResult};
+use crate::{AnalysisError, Result};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::io::{Cursor, Read, Write};
+use std::path::{Path, PathBuf};
+use uuid::Uuid;
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipArchive, ZipWriter};
+
+/// Part holding the main document body.
+const DOCUMENT_PART: &str = "word/document.xml";
+/// Part holding the core document properties (title, subject, keywords...).
+const CORE_PART: &str = "docProps/core.xml";
+/// Profiles with a DOCX equivalent; the rest are rejected.
+const SUPPORTED_PROFILES: &[&str] = &["pdf.visible_meta_block", "pdf.low_visibility_block", "pdf.structural_fields"];
+/// Upper bound on the uncompressed size of one package entry.
+const MAX_PART_BYTES: u64 = 64 * 1024 * 1024;
+/// Upper bound on the uncompressed size of the whole package.
+const MAX_PACKAGE_BYTES: u64 = 256 * 1024 * 1024;
+
+/// Mutates DOCX (Office Open XML) resumes by editing the document and core
+/// properties parts and rezipping the package.
+pub struct DocxMutator {
+    /// Directory where mutated documents will be saved.
+    pub output_dir: PathBuf,
+}
+
+impl DocxMutator {
+    /// Creates a new `DocxMutator` with the specified output directory.
+    pub fn new(output_dir: impl Into<PathBuf>) -> Self {
+        DocxMutator {
+            output_dir: output_dir.into(),
+        }
+    }
+}
+
+impl DocumentMutator for DocxMutator {
+    fn mutate(&self, request: PdfMutationRequest) -> Result<PdfMutationResult> {
+        let variant_id = request
+            .variant_id
+            .clone()
+            .unwrap_or_else(|| Uuid::new_v4().to_string());
+        let (bytes, notes) = mutate_docx_to_bytes(&request)?;
+
+        fs::create_dir_all(&self.output_dir)?;
+        let output_path = self.output_dir.join(format!("{}.docx", variant_id));
+        fs::write(&output_path, &bytes)?;
+
+        let mut hasher = Sha256::new();
+        hasher.update(&bytes);
+
+        Ok(PdfMutationResult {
+            variant_id,
+            mutated_pdf: output_path,
+            variant_hash: Some(format!("{:x}", hasher.finalize())),
+            notes,
+            watermark_applied: false,
+        })
+    }
+
+    fn supported_profiles(&self) -> &[&'static str] {
+        SUPPORTED_PROFILES
+    }
+}
+
+/// Returns true when `path` has a `.docx` extension.
+pub fn is_docx(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("docx"))
+}
+
+/// Applies the request's profiles to the base DOCX and returns the rezipped
+/// package along with notes. Only profiles with a text-layer equivalent in
+/// Word are supported; anything else is rejected.
+pub fn mutate_docx_to_bytes(request: &PdfMutationRequest) -> Result<(Vec<u8>, Vec<String>)> {
+    if request.password.is_some() || request.output_password.is_some() {
+        return Err(AnalysisError::DocxError(
+            "password-protected DOCX files are not supported".to_string(),
+        ));
+    }
+
+    let mut parts = read_parts(&fs::read(&request.base_pdf)?)?;
+    let mut notes = Vec::new();
+    if request.watermark.is_some() {
+        notes.push("Watermarks are not supported for DOCX; skipped".to_string());
+    }
+    if request.profiles.iter().any(fragments_text) {
+        notes.push("Fragmented text is not supported for DOCX; wrote whole runs".to_string());
+    }
+    if request.profiles.iter().any(repeats_per_page) {
+        notes.push("Per-page repetition does not apply to DOCX; wrote each block once".to_string());
+    }
+    if request.incremental {
+        notes.push("Incremental updates do not apply to DOCX; rewrote the package".to_string());
+    }
+
+    let seed = request_seed(request, &mut notes);
+    let mut rng = StdRng::seed_from_u64(seed);
+    let default_text = &request.template.text_template;
+    let mut document = part_text(&parts, DOCUMENT_PART)?;
+    let mut core = None;
+
+    for profile in &request.profiles {
+        match profile {
+            ProfileConfig::VisibleMetaBlock { position, content, .. } => {
+                let text = get_injection_text(content, default_text, seed, &mut rng, &mut notes);
+                let paragraph = paragraph_xml(&text, None);
+                match position {
+                    InjectionPosition::Header => insert_at_body_start(&mut document, &paragraph)?,
+                    _ => insert_at_body_end(&mut document, &paragraph)?,
+                }
+                notes.push(format!("Appended visible paragraph at {:?}", position));
+            }
+            ProfileConfig::LowVisibilityBlock { content, .. } => {
+                let text = get_injection_text(content, default_text, seed, &mut rng, &mut notes);
+                // w:sz is in half-points, so 2 is a 1pt run.
+                let paragraph = paragraph_xml(&text, Some(r#"<w:rPr><w:color w:val="FFFFFF"/><w:sz w:val="2"/><w:szCs w:val="2"/></w:rPr>"#));
+                insert_at_body_end(&mut document, &paragraph)?;
+                notes.push("Appended low visibility paragraph (white, 1pt)".to_string());
+            }
+            ProfileConfig::StructuralFields { targets } => {
+                let core = match &mut core {
+                    Some(core) => core,
+                    None => core.insert(part_text(&parts, CORE_PART)?),
+                };
+                for target in targets {
+                    let (element, label) = match target {
+                        StructuralTarget::AltText => ("dc:description", "description"),
+                        StructuralTarget::PdfTag => ("cp:keywords", "keywords"),
+                        StructuralTarget::XmpMetadata => ("dc:subject", "subject"),
+                    };
+                    set_core_property(core, element, default_text)?;
+                    notes.push(format!("Injected into core.xml {}", label));
+                }
+            }
+            other => return Err(AnalysisError::UnsupportedProfile(format!("{} (docx)", other.id()))),
+        }
+    }
+
+    set_part(&mut parts, DOCUMENT_PART, document);
+    if let Some(core) = core {
+        set_part(&mut parts, CORE_PART, core);
+    }
+    Ok((write_parts(&parts)?, notes))
+}
+
+/// Extracts the body text of a DOCX file, one line per paragraph.
+pub fn extract_text_from_docx(path: &Path) -> Result<String> {
+    let parts = read_parts(&fs::read(path)?)?;
+    let document = part_text(&parts, DOCUMENT_PART)?;
+
+    let mut text = String::new();
+    let mut rest = document.as_str();
+    while let Some(start) = rest.find('<') {
+        let Some(end) = rest[start..].find('>') else { break };
+        let tag = &rest[start + 1..start + end];
+        let name = tag.split(|c: char| c.is_whitespace() || c == '/').next().unwrap_or("");
+        rest = &rest[start + end + 1..];
+        match name {
+            "w:t" if !tag.ends_with('/') => {
+                let close = rest.find("</w:t>").unwrap_or(rest.len());
+                text.push_str(&unescape_xml(&rest[..close]));
+                rest = &rest[close..];
+            }
+            // Tab stops in paragraph properties are also `w:tab`, but carry attributes.
+            "w:tab" if tag.trim_end_matches('/').trim_end() == "w:tab" => text.push('\t'),
+            "w:br" | "w:cr" => text.push('\n'),
+            _ if tag == "/w:p" => text.push('\n'),
+            _ => {}
+        }
+    }
+    Ok(text)
+}
+
+/// Builds a minimal single-paragraph DOCX package, mainly for tests and demos.
+pub fn create_blank_docx(text: &str) -> Result<Vec<u8>> {
+    let document = format!(
+        r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<w:document xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main"><w:body>{}<w:sectPr/></w:body></w:document>"#,
+        paragraph_xml(text, None)
+    );
+    let parts = vec![
+        (
+            "[Content_Types].xml".to_string(),
+            br#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<Types xmlns="http://schemas.openxmlformats.org/package/2006/content-types"><Default Extension="rels" ContentType="application/vnd.openxmlformats-package.relationships+xml"/><Default Extension="xml" ContentType="application/xml"/><Override PartName="/word/document.xml" ContentType="application/vnd.openxmlformats-officedocument.wordprocessingml.document.main+xml"/><Override PartName="/docProps/core.xml" ContentType="application/vnd.openxmlformats-package.core-properties+xml"/></Types>"#.to_vec(),
+        ),
+        (
+            "_rels/.rels".to_string(),
+            br#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships"><Relationship Id="rId1" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/officeDocument" Target="word/document.xml"/><Relationship Id="rId2" Type="http://schemas.openxmlformats.org/package/2006/relationships/metadata/core-properties" Target="docProps/core.xml"/></Relationships>"#.to_vec(),
+        ),
+        (DOCUMENT_PART.to_string(), document.into_bytes()),
+        (
+            CORE_PART.to_string(),
+            br#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<cp:coreProperties xmlns:cp="http://schemas.openxmlformats.org/package/2006/metadata/core-properties" xmlns:dc="http://purl.org/dc/elements/1.1/"><dc:title>Resume</dc:title></cp:coreProperties>"#.to_vec(),
+        ),
+    ];
+    write_parts(&parts)
+}
+
+/// Reads every entry of the package in archive order. Entries inflating past
+/// `MAX_PART_BYTES`, or a package past `MAX_PACKAGE_BYTES`, are rejected
+/// whatever size their headers claim.
+fn read_parts(bytes: &[u8]) -> Result<Vec<(String, Vec<u8>)>> {
+    let mut archive = ZipArchive::new(Cursor::new(bytes)).map_err(docx_error)?;
+    let mut parts = Vec::with_capacity(archive.len());
+    let mut total = 0;
+    for index in 0..archive.len() {
+        let entry = archive.by_index(index).map_err(docx_error)?;
+        let name = entry.name().map_err(docx_error)?.into_owned();
+        let limit = MAX_PART_BYTES.min(MAX_PACKAGE_BYTES - total);
+        if entry.size() > limit {
+            return Err(too_large(&name, limit));
+        }
+        let mut data = Vec::new();
+        entry.take(limit + 1).read_to_end(&mut data)?;
+        if data.len() as u64 > limit {
+            return Err(too_large(&name, limit));
+        }
+        total += data.len() as u64;
+        parts.push((name, data));
+    }
+    Ok(parts)
+}
+
+fn too_large(name: &str, limit: u64) -> AnalysisError {
+    AnalysisError::DocxError(format!("DOCX entry {} exceeds the limit of {} bytes", name, limit))
+}
+
+/// Rezips the package, keeping the original entry order so
+/// `[Content_Types].xml` stays first.
+fn write_parts(parts: &[(String, Vec<u8>)]) -> Result<Vec<u8>> {
+    let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+    let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+    for (name, data) in parts {
+        writer.start_file(name.as_str(), options).map_err(docx_error)?;
+        writer.write_all(data)?;
+    }
+    Ok(writer.finish().map_err(docx_error)?.into_inner())
+}
+
+fn part_text(parts: &[(String, Vec<u8>)], name: &str) -> Result<String> {
+    let (_, data) = parts
+        .iter()
+        .find(|(part, _)| part == name)
+        .ok_or_else(|| AnalysisError::DocxError(format!("DOCX is missing {}", name)))?;
+    String::from_utf8(data.clone()).map_err(|e| AnalysisError::DocxError(format!("{} is not UTF-8: {}", name, e)))
+}
+
+fn set_part(parts: &mut [(String, Vec<u8>)], name: &str, text: String) {
+    if let Some((_, data)) = parts.iter_mut().find(|(part, _)| part == name) {
+        *data = text.into_bytes();
+    }
+}
+
+/// A `<w:p>` holding `text` in one run, with line breaks kept as `<w:br/>`.
+fn paragraph_xml(text: &str, run_properties: Option<&str>) -> String {
+    let runs = text
+        .lines()
+        .map(|line| format!(r#"<w:t xml:space="preserve">{}</w:t>"#, escape_xml(line)))
+        .collect::<Vec<_>>()
+        .join("<w:br/>");
+    format!("<w:p><w:r>{}{}</w:r></w:p>", run_properties.unwrap_or(""), runs)
+}
+
+fn insert_at_body_start(document: &mut String, xml: &str) -> Result<()> {
+    let body = document.find("<w:body").ok_or_else(missing_body)?;
+    let open_end = body + document[body..].find('>').ok_or_else(missing_body)? + 1;
+    document.insert_str(open_end, xml);
+    Ok(())
+}
+
+/// Inserts before the body's trailing `<w:sectPr>`, which Word requires to
+/// stay the last child of `<w:body>`.
+fn insert_at_body_end(document: &mut String, xml: &str) -> Result<()> {
+    let close = document.rfind("</w:body>").ok_or_else(missing_body)?;
+    let at = body_sect_pr_start(&document[..close]).unwrap_or(close);
+    document.insert_str(at, xml);
+    Ok(())
+}
+
+/// Start of the `<w:sectPr>` ending `body`, i.e. the body-level one. Section
+/// breaks inside a paragraph's `<w:pPr>` never end the body and are skipped.
+fn body_sect_pr_start(body: &str) -> Option<usize> {
+    let body = body.trim_end();
+    if body.ends_with("/>") {
+        let start = body.rfind('<')?;
+        return is_sect_pr_tag(&body[start..]).then_some(start);
+    }
+    if !body.ends_with("</w:sectPr>") {
+        return None;
+    }
+    // Walk back to the matching start tag: a `<w:sectPrChange>` inside holds
+    // a `<w:sectPr>` of its own.
+    let mut depth = 0;
+    let mut end = body.len();
+    while let Some(start) = body[..end].rfind('<') {
+        let tag = &body[start..end];
+        let tag = &tag[..tag.find('>').map_or(tag.len(), |close| close + 1)];
+        if tag == "</w:sectPr>" {
+            depth += 1;
+        } else if is_sect_pr_tag(tag) && !tag.ends_with("/>") {
+            depth -= 1;
+            if depth == 0 {
+                return Some(start);
+            }
+        }
+        end = start;
+    }
+    None
+}
+
+/// Whether `tag` opens a `w:sectPr` element (and not e.g. `w:sectPrChange`).
+fn is_sect_pr_tag(tag: &str) -> bool {
+    tag.strip_prefix("<w:sectPr")
+        .is_some_and(|rest| rest.starts_with(|c: char| c == '>' || c == '/' || c.is_ascii_whitespace()))
+}
+
+/// Replaces the content of `element` in core.xml, adding it when absent.
+fn set_core_property(core: &mut String, element: &str, value: &str) -> Result<()> {
+    let value = escape_xml(value);
+    let open = format!("<{}", element);
+    let close = format!("</{}>", element);
+    if let Some(start) = core.find(&open) {
+        let tag_end = start + core[start..].find('>').ok_or_else(missing_core)? + 1;
+        if core[..tag_end].ends_with("/>") {
+            core.replace_range(start..tag_end, &format!("<{0}>{1}</{0}>", element, value));
+        } else {
+            let end = tag_end + core[tag_end..].find(&close).ok_or_else(missing_core)?;
+            core.replace_range(tag_end..end, &value);
+        }
+    } else {
+        let at = core.rfind("</cp:coreProperties>").ok_or_else(missing_core)?;
+        core.insert_str(at, &format!("<{0}>{1}</{0}>", element, value));
+    }
+    Ok(())
+}
+
+fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn unescape_xml(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+fn missing_body() -> AnalysisError {
+    AnalysisError::DocxError("DOCX document.xml has no <w:body>".to_string())
+}
+
+fn missing_core() -> AnalysisError {
+    AnalysisError::DocxError("DOCX core.xml is malformed".to_string())
+}
+
+fn docx_error(e: zip::result::ZipError) -> AnalysisError {
+    AnalysisError::DocxError(format!("invalid DOCX package: {}", e))
+}
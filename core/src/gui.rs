@@ -5,11 +5,14 @@ pub mod components;
 use eframe::egui;
 use std::path::PathBuf;
 use std::fs::File;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc};
 use crate::generator::{self, ScrapedProfile};
-use crate::attacks::{ProfileConfig, InjectionContent, LowVisibilityPalette, OffpageOffset, StructuralTarget, PaddingStyle, JobAdSource, JobAdPlacement};
-use crate::attacks::templates::{GenerationType, default_templates};
+use crate::attacks::{ProfileConfig, ProfileConfigBuilder, StructuralTarget};
+use crate::attacks::templates::GenerationType;
 use crate::config::AppConfig;
-use crate::pdf::{PdfMutator, RealPdfMutator, PdfMutationRequest};
+use crate::llm::LlmUsage;
+use crate::api;
 use crate::latex::LatexResume;
 
 use self::types::{InputSource, LlmProvider, InjectionConfigGui, InjectionTypeGui, ProfileMask};
@@ -35,8 +38,9 @@ pub fn run_gui() -> eframe::Result<()> {
         options,
         Box::new(|cc| {
             setup_custom_fonts(&cc.egui_ctx);
-            setup_custom_styles(&cc.egui_ctx);
-            Ok(Box::new(MyApp::default()))
+            let app = MyApp::default();
+            setup_custom_styles(&cc.egui_ctx, &app.config.theme);
+            Ok(Box::new(app))
         }),
     )
 }
@@ -86,6 +90,14 @@ struct MyApp {
     profile_editor_pinned: bool,
     loaded_profile: Option<ScrapedProfile>,
     profile_mask: ProfileMask,
+
+    // Background generation; `Some` while a worker thread is running
+    generation_rx: Option<mpsc::Receiver<String>>,
+    // Set to ask the running worker to stop at its next checkpoint
+    generation_cancel: Arc<AtomicBool>,
+
+    // Tokens spent on LLM calls this session
+    llm_usage: LlmUsage,
 }
 
 impl Default for MyApp {
@@ -121,17 +133,25 @@ impl Default for MyApp {
             profile_editor_pinned: false,
             loaded_profile: None,
             profile_mask: ProfileMask::default(),
+            generation_rx: None,
+            generation_cancel: Arc::new(AtomicBool::new(false)),
+            llm_usage: LlmUsage::default(),
         }
     }
 }
 
 impl eframe::App for MyApp {
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        self.poll_generation();
+
         // Main Window Custom Frame
         let mut pinned = self.main_pinned;
         custom_window_frame(ctx, "SUPERPOWERED_CV", |ui| {
             let mut action = None;
+            let mut cancel_requested = false;
+            let mut recent_preset = None;
             let config_clone = self.config.clone();
+            let generating = self.is_generating();
             
             render_main_content(
                 ui,
@@ -146,14 +166,25 @@ impl eframe::App for MyApp {
                 &mut self.show_ai_assistant,
                 &mut self.show_ats_dashboard,
                 |msg| self.status_log.push(format!("> {}", msg)),
+                generating,
                 || { action = Some(()); },
+                || { cancel_requested = true; },
                 &mut self.loaded_profile,
                 &mut self.profile_mask,
+                &mut self.llm_usage,
                 |path| self.config.add_recent_file(&path),
+                |path| recent_preset = Some(path),
             );
+
+            if let Some(path) = recent_preset {
+                self.config.add_recent_preset(&path);
+            }
             
             if action.is_some() {
-                self.generate();
+                self.generate(ui.ctx());
+            }
+            if cancel_requested {
+                self.cancel_generation();
             }
 
         }, &mut pinned);
@@ -206,7 +237,7 @@ impl eframe::App for MyApp {
                 builder,
                 |ctx, _class| {
                     custom_window_frame(ctx, "LATEX_VISUAL_BUILDER", |ui| {
-                        render_latex_builder(ui, &mut self.latex_resume, &self.input_source);
+                        render_latex_builder(ui, &mut self.latex_resume, &self.input_source, &self.config.latex, |msg| self.status_log.push(format!("> {}", msg)));
                     }, &mut pinned);
                     
                     if ctx.input(|i| i.viewport().close_requested()) {
@@ -271,7 +302,7 @@ impl eframe::App for MyApp {
                 |ctx, _class| {
                     custom_window_frame(ctx, "AI_ASSISTANT", |ui| {
                         if let Some(profile) = &mut self.loaded_profile {
-                            render_ai_assistant(ui, &mut self.ai_assistant_state, profile, &self.config, &mut |msg| self.status_log.push(format!("> {}", msg)));
+                            render_ai_assistant(ui, &mut self.ai_assistant_state, profile, &self.config, &mut self.llm_usage, &mut |msg| self.status_log.push(format!("> {}", msg)));
                         } else {
                             ui.label("Please load a profile first.");
                         }
@@ -350,8 +381,96 @@ impl MyApp {
         self.status_log.push(format!("> {}", msg));
     }
 
-    fn generate(&mut self) {
-        self.log("STARTING PIPELINE...");
+    fn is_generating(&self) -> bool {
+        self.generation_rx.is_some()
+    }
+
+    /// Snapshots the current inputs and runs the pipeline on a worker thread.
+    fn generate(&mut self, ctx: &egui::Context) {
+        if self.is_generating() {
+            return;
+        }
+        let Some(output) = self.output_path.clone() else {
+            self.log("Error: No output path selected.");
+            return;
+        };
+
+        let profiles = match build_profiles(&self.injections) {
+            Ok(profiles) => profiles,
+            Err(e) => {
+                self.log(&format!("Error: {}", e));
+                return;
+            }
+        };
+
+        // Each job gets its own flag so a late cancel cannot stop the next run.
+        self.generation_cancel = Arc::new(AtomicBool::new(false));
+        let job = GenerationJob {
+            input_source: self.input_source.clone(),
+            loaded_profile: self.loaded_profile.clone(),
+            profile_mask: self.profile_mask.clone(),
+            profiles,
+            output,
+            cancel: Arc::clone(&self.generation_cancel),
+        };
+
+        let (tx, rx) = mpsc::channel();
+        let ctx = ctx.clone();
+        std::thread::spawn(move || {
+            job.run(|msg| {
+                let _ = tx.send(msg.to_string());
+                ctx.request_repaint();
+            });
+            // Dropping the sender disconnects the channel, which marks the job finished.
+            ctx.request_repaint();
+        });
+        self.generation_rx = Some(rx);
+    }
+
+    /// Asks the running worker to stop before its next pipeline stage.
+    fn cancel_generation(&mut self) {
+        if self.is_generating() && !self.generation_cancel.swap(true, Ordering::SeqCst) {
+            self.log("Cancelling generation...");
+        }
+    }
+
+    /// Drains progress messages from the worker, clearing the job once it has exited.
+    fn poll_generation(&mut self) {
+        let Some(rx) = &self.generation_rx else { return };
+        let mut finished = false;
+        loop {
+            match rx.try_recv() {
+                Ok(msg) => self.status_log.push(format!("> {}", msg)),
+                Err(mpsc::TryRecvError::Empty) => break,
+                Err(mpsc::TryRecvError::Disconnected) => {
+                    finished = true;
+                    break;
+                }
+            }
+        }
+        if finished {
+            self.generation_rx = None;
+        }
+    }
+}
+
+/// Everything the generation pipeline needs, detached from `MyApp` so it can move to a worker thread.
+struct GenerationJob {
+    input_source: InputSource,
+    loaded_profile: Option<ScrapedProfile>,
+    profile_mask: ProfileMask,
+    profiles: Vec<ProfileConfig>,
+    output: PathBuf,
+    cancel: Arc<AtomicBool>,
+}
+
+impl GenerationJob {
+    fn is_cancelled(&self) -> bool {
+        self.cancel.load(Ordering::SeqCst)
+    }
+
+    fn run(self, log: impl Fn(&str)) {
+        log("STARTING PIPELINE...");
         
         // 1. Determine Base PDF
         let base_pdf_path = match &self.input_source {
@@ -359,141 +478,92 @@ impl MyApp {
                 // Generate temp PDF from JSON
                 let file = match File::open(path) {
                     Ok(f) => f,
-                    Err(e) => { self.log(&format!("Error opening JSON: {}", e)); return; }
+                    Err(e) => { log(&format!("Error opening JSON: {}", e)); return; }
                 };
-                let mut profile: ScrapedProfile = match serde_json::from_reader(file) {
+                let profile: ScrapedProfile = match serde_json::from_reader(file) {
                     Ok(p) => p,
-                    Err(e) => { self.log(&format!("Error parsing JSON: {}", e)); return; }
+                    Err(e) => { log(&format!("Error parsing JSON: {}", e)); return; }
                 };
+                // The mask indexes the profile loaded in the editor, so render that one when present
+                let profile = self.loaded_profile.as_ref().unwrap_or(&profile);
 
-                // Apply Profile Mask
-                if let Some(loaded) = &self.loaded_profile {
-                    // We use the loaded profile if available, as it might be the same one
-                    // But wait, generate_pdf takes a profile.
-                    // We should filter the profile based on the mask.
-                    
-                    let mut filtered_profile = loaded.clone(); // Assuming ScrapedProfile is Clone, which it is derived
-                    
-                    // Filter Experience
-                    let mut new_exp = Vec::new();
-                    for (i, exp) in filtered_profile.experience.iter().enumerate() {
-                        if i < self.profile_mask.experience_enabled.len() && self.profile_mask.experience_enabled[i] {
-                            new_exp.push(exp.clone()); // ScrapedExperience needs Clone
-                        }
-                    }
-                    filtered_profile.experience = new_exp;
-
-                    // Filter Education
-                    let mut new_edu = Vec::new();
-                    for (i, edu) in filtered_profile.education.iter().enumerate() {
-                        if i < self.profile_mask.education_enabled.len() && self.profile_mask.education_enabled[i] {
-                            new_edu.push(edu.clone()); // ScrapedEducation needs Clone
-                        }
-                    }
-                    filtered_profile.education = new_edu;
-
-                    // Filter Skills
-                    let mut new_skills = Vec::new();
-                    for (i, skill) in filtered_profile.skills.iter().enumerate() {
-                        if i < self.profile_mask.skills_enabled.len() && self.profile_mask.skills_enabled[i] {
-                            new_skills.push(skill.clone());
-                        }
-                    }
-                    filtered_profile.skills = new_skills;
-                    
-                    profile = filtered_profile;
-                }
-                
                 let temp_path = std::env::temp_dir().join("superpoweredcv_temp.pdf");
-                if let Err(e) = generator::generate_pdf(&profile, &temp_path, None) {
-                    self.log(&format!("Error generating base PDF: {}", e));
+                if let Err(e) = generator::generate_pdf_masked(profile, Some(&self.profile_mask), &temp_path, None) {
+                    log(&format!("Error generating base PDF: {}", e));
                     return;
                 }
+                log("Base PDF generated from profile.");
                 temp_path
             }
             InputSource::PdfFile(Some(path)) => path.clone(),
             InputSource::LinkedinUrl(_) => {
-                self.log("Error: URL input not implemented yet.");
+                log("Error: URL input not implemented yet.");
                 return;
             }
             _ => {
-                self.log("Error: No input selected.");
+                log("Error: No input selected.");
                 return;
             }
         };
 
-        // 2. Build Profiles
-        let mut profiles = Vec::new();
-        for inj in &self.injections {
-            let content = InjectionContent {
-                phrases: inj.phrases.clone(),
-                generation_type: inj.generation_type.clone(),
-                job_description: if inj.generation_type == GenerationType::AdTargeted { Some(inj.job_description.clone()) } else { None },
-            };
-
-            let profile = match inj.injection_type {
-                InjectionTypeGui::VisibleMetaBlock => ProfileConfig::VisibleMetaBlock {
-                    position: inj.position.clone(),
-                    intensity: inj.intensity.clone(),
-                    content,
-                },
-                InjectionTypeGui::LowVisibilityBlock => ProfileConfig::LowVisibilityBlock {
-                    font_size_min: 1,
-                    font_size_max: 1,
-                    color_profile: LowVisibilityPalette::Gray,
-                    content,
-                },
-                InjectionTypeGui::OffpageLayer => ProfileConfig::OffpageLayer {
-                    offset_strategy: OffpageOffset::BottomClip,
-                    content,
-                },
-                InjectionTypeGui::UnderlayText => ProfileConfig::UnderlayText,
-                InjectionTypeGui::StructuralFields => ProfileConfig::StructuralFields {
+        if self.is_cancelled() {
+            log("CANCELLED: Generation stopped before injection.");
+            return;
+        }
+
+        // 2. Mutate
+        log(&format!("Applying {} injection module(s)...", self.profiles.len()));
+        match api::inject_pdf(&base_pdf_path, &self.output, self.profiles, api::default_template()) {
+            Ok(_) => log("SUCCESS: PDF Generated & Injected."),
+            Err(e) => log(&format!("Error mutating PDF: {}", e)),
+        }
+    }
+}
+
+/// Translates the GUI module settings into attack profiles.
+fn build_profiles(injections: &[InjectionConfigGui]) -> crate::Result<Vec<ProfileConfig>> {
+    let mut profiles = Vec::new();
+    for inj in injections {
+        let builder = match inj.injection_type {
+            InjectionTypeGui::VisibleMetaBlock => ProfileConfigBuilder::visible_meta()
+                .with_position(inj.position.clone())
+                .with_intensity(inj.intensity.clone()),
+            InjectionTypeGui::LowVisibilityBlock => ProfileConfigBuilder::low_visibility(),
+            InjectionTypeGui::OffpageLayer => ProfileConfigBuilder::offpage(),
+            InjectionTypeGui::PaddingNoise => ProfileConfigBuilder::padding_noise().with_intensity(inj.intensity.clone()),
+            InjectionTypeGui::InlineJobAd => ProfileConfigBuilder::inline_job_ad(),
+            InjectionTypeGui::UnderlayText => {
+                profiles.push(ProfileConfig::UnderlayText);
+                continue;
+            }
+            InjectionTypeGui::StructuralFields => {
+                profiles.push(ProfileConfig::StructuralFields {
                     targets: vec![StructuralTarget::PdfTag], // Default for now
-                },
-                InjectionTypeGui::PaddingNoise => ProfileConfig::PaddingNoise {
-                    padding_tokens_before: 100,
-                    padding_tokens_after: 100,
-                    padding_style: PaddingStyle::JobRelated,
-                    content,
-                },
-                InjectionTypeGui::InlineJobAd => ProfileConfig::InlineJobAd {
-                    job_ad_source: JobAdSource::Inline,
-                    placement: JobAdPlacement::Back,
-                    ad_excerpt_ratio: 1.0,
-                    content,
-                },
-                InjectionTypeGui::TrackingPixel => ProfileConfig::TrackingPixel {
+                });
+                continue;
+            }
+            InjectionTypeGui::TrackingPixel => {
+                profiles.push(ProfileConfig::TrackingPixel {
                     url: "https://canarytokens.org/pixel".to_string(), // Default placeholder
-                },
-                InjectionTypeGui::CodeInjection => ProfileConfig::CodeInjection {
+                    cover_whole_page: true,
+                });
+                continue;
+            }
+            InjectionTypeGui::CodeInjection => {
+                profiles.push(ProfileConfig::CodeInjection {
                     payload: "alert('XSS')".to_string(), // Default placeholder
-                },
-            };
-            profiles.push(profile);
-        }
-
-        // 3. Mutate
-        let output = self.output_path.as_ref().unwrap();
-        let mutator = RealPdfMutator::new(output.parent().unwrap());
-        
-        let request = PdfMutationRequest {
-            base_pdf: base_pdf_path,
-            profiles,
-            template: default_templates().into_iter().find(|t| t.id == "default").unwrap_or_else(|| default_templates()[0].clone()),
-            variant_id: Some(output.file_stem().unwrap().to_string_lossy().to_string()),
+                });
+                continue;
+            }
         };
 
-        match mutator.mutate(request) {
-            Ok(res) => {
-                // Rename result to final output
-                if let Err(e) = std::fs::rename(&res.mutated_pdf, output) {
-                    self.log(&format!("Error moving file: {}", e));
-                } else {
-                    self.log("SUCCESS: PDF Generated & Injected.");
-                }
-            }
-            Err(e) => self.log(&format!("Error mutating PDF: {}", e)),
+        let mut builder = builder
+            .with_phrases(inj.phrases.iter().cloned())
+            .with_generation_type(inj.generation_type.clone());
+        if inj.generation_type == GenerationType::AdTargeted {
+            builder = builder.with_job_description(inj.job_description.clone());
         }
+        profiles.push(builder.build()?);
     }
+    Ok(profiles)
 }
@@ -1,5 +1,5 @@
 use eframe::egui;
-use crate::llm::LlmClient;
+use crate::llm::{LlmClient, LlmUsage};
 use crate::config::AppConfig;
 use crate::generator::{ScrapedProfile, ScrapedExperience};
 
@@ -28,6 +28,7 @@ pub fn render_ai_assistant(
     state: &mut AiAssistantState,
     profile: &mut ScrapedProfile,
     config: &AppConfig,
+    llm_usage: &mut LlmUsage,
     log_fn: &mut impl FnMut(&str),
 ) {
     ui.group(|ui| {
@@ -47,15 +48,16 @@ pub fn render_ai_assistant(
                 // Let's try to do it "blocking" but warn the user, or ideally spawn a thread and use a channel.
                 // For this refactor, I'll keep it simple but acknowledge the freeze.
                 
-                let client = LlmClient::new(config.llm.clone());
+                let client = LlmClient::with_network(config.llm.clone(), &config.network);
                 let prompt = format!(
                     "Review the following CV profile and provide constructive feedback on strengths, weaknesses, and ATS optimization:\n\n{}",
                     serde_json::to_string_pretty(profile).unwrap_or_default()
                 );
 
-                match client.generate(&prompt) {
+                match client.generate_with_usage(&prompt) {
                     Ok(response) => {
-                        state.review_result = Some(response);
+                        llm_usage.record(&response);
+                        state.review_result = Some(response.text);
                         log_fn("AI Review Completed.");
                     }
                     Err(e) => {
@@ -98,15 +100,16 @@ pub fn render_ai_assistant(
         ui.label("Summary Rewrite:");
         ui.horizontal(|ui| {
             if ui.button("REWRITE SUMMARY").clicked() {
-                 let client = LlmClient::new(config.llm.clone());
+                 let client = LlmClient::with_network(config.llm.clone(), &config.network);
                  let prompt = format!(
                     "Rewrite the following professional summary to be more impactful, concise, and action-oriented:\n\n{}",
                     profile.about
                 );
                 
-                match client.generate(&prompt) {
+                match client.generate_with_usage(&prompt) {
                     Ok(response) => {
-                        profile.about = response; // Direct apply for now, or show diff
+                        llm_usage.record(&response);
+                        profile.about = response.text; // Direct apply for now, or show diff
                         log_fn("Summary Rewritten.");
                     }
                     Err(e) => {
@@ -1,6 +1,8 @@
 use eframe::egui;
 use std::fs::File;
-use crate::latex::LatexResume;
+use crate::config::LatexConfig;
+use crate::latex::{LatexResume, ProjectItem};
+use crate::latex::manager::LatexManager;
 use crate::generator::ScrapedProfile;
 use crate::gui::types::InputSource;
 
@@ -14,11 +16,23 @@ use crate::gui::types::InputSource;
 /// * `ui` - The egui Ui context.
 /// * `latex_resume` - The mutable state of the resume being built.
 /// * `input_source` - The source of data (e.g., JSON file) to import from.
-pub fn render_latex_builder(ui: &mut egui::Ui, latex_resume: &mut LatexResume, input_source: &InputSource) {
+/// * `latex_config` - The engine and binary used to export the PDF.
+/// * `log_fn` - Callback receiving export progress and compilation errors.
+pub fn render_latex_builder(
+    ui: &mut egui::Ui,
+    latex_resume: &mut LatexResume,
+    input_source: &InputSource,
+    latex_config: &LatexConfig,
+    mut log_fn: impl FnMut(&str),
+) {
     ui.columns(2, |columns| {
         // Left Column: Editor
         columns[0].vertical(|ui| {
             render_editor_panel(ui, latex_resume, input_source);
+            ui.separator();
+            if ui.button("EXPORT PDF").clicked() {
+                export_pdf(latex_resume, latex_config, &mut log_fn);
+            }
         });
 
         // Right Column: Preview (Placeholder for now, or simplified view)
@@ -67,42 +81,167 @@ fn render_editor_panel(ui: &mut egui::Ui, latex_resume: &mut LatexResume, input_
 
         ui.separator();
         ui.heading(egui::RichText::new("Sections").color(egui::Color32::from_rgb(255, 69, 0)));
-        ui.label(egui::RichText::new("Drag sections to reorder (Not implemented in this version)").small().italics());
-        
+        ui.label(egui::RichText::new("Drag ☰ handles to reorder sections and items").small().italics());
+
+        let section_count = latex_resume.sections.len();
         let mut section_to_remove = None;
+        let mut section_move = None;
+        let mut item_move = None;
         for (idx, section) in latex_resume.sections.iter_mut().enumerate() {
             ui.push_id(section.id.clone(), |ui| {
-                ui.group(|ui| {
+                let group = ui.group(|ui| {
                     ui.horizontal(|ui| {
+                        ui.dnd_drag_source(ui.id().with("drag"), SectionDrag(idx), |ui| {
+                            ui.label("☰");
+                        });
                         ui.text_edit_singleline(&mut section.title);
+                        if ui.add_enabled(idx > 0, egui::Button::new("⏶")).clicked() {
+                            section_move = Some((idx, idx - 1));
+                        }
+                        if ui.add_enabled(idx + 1 < section_count, egui::Button::new("⏷")).clicked() {
+                            section_move = Some((idx, idx + 1));
+                        }
                         if ui.button("🗑").clicked() {
                             section_to_remove = Some(idx);
                         }
                     });
                     
-                    for item in &mut section.items {
+                    let item_count = section.items.len();
+                    for (item_idx, item) in section.items.iter_mut().enumerate() {
                         ui.push_id(item.id.clone(), |ui| {
-                            let title = item.title.clone();
-                            ui.collapsing(title, |ui| {
-                                ui.text_edit_singleline(&mut item.title);
-                                ui.text_edit_singleline(&mut item.subtitle);
-                                ui.text_edit_singleline(&mut item.date);
-                                for desc in &mut item.description {
-                                    ui.text_edit_multiline(desc);
+                            let row = ui.horizontal(|ui| {
+                                ui.dnd_drag_source(ui.id().with("drag"), ItemDrag { section: idx, item: item_idx }, |ui| {
+                                    ui.label("☰");
+                                });
+                                if ui.add_enabled(item_idx > 0, egui::Button::new("⏶")).clicked() {
+                                    item_move = Some((idx, item_idx, item_idx - 1));
+                                }
+                                if ui.add_enabled(item_idx + 1 < item_count, egui::Button::new("⏷")).clicked() {
+                                    item_move = Some((idx, item_idx, item_idx + 1));
                                 }
-                            });
+                                let title = item.title.clone();
+                                ui.collapsing(title, |ui| {
+                                    ui.text_edit_singleline(&mut item.title);
+                                    ui.text_edit_singleline(&mut item.subtitle);
+                                    ui.text_edit_singleline(&mut item.date);
+                                    for desc in &mut item.description {
+                                        ui.text_edit_multiline(desc);
+                                    }
+                                });
+                            }).response;
+
+                            if let Some(dragged) = drop_payload::<ItemDrag>(ui, &row) {
+                                // Items only move within their own section.
+                                if dragged.section == idx {
+                                    item_move = Some((idx, dragged.item, item_idx));
+                                }
+                            }
                         });
                     }
-                });
+                }).response;
+
+                if let Some(dragged) = drop_payload::<SectionDrag>(ui, &group) {
+                    section_move = Some((dragged.0, idx));
+                }
             });
         }
 
+        if let Some((from, to)) = section_move {
+            latex_resume.move_section(from, to);
+        }
+        if let Some((section, from, to)) = item_move {
+            latex_resume.move_item(section, from, to);
+        }
         if let Some(idx) = section_to_remove {
             latex_resume.sections.remove(idx);
         }
+
+        ui.separator();
+        ui.heading(egui::RichText::new("Skills").color(egui::Color32::from_rgb(255, 69, 0)));
+        let mut skills = latex_resume.skills.join(", ");
+        if ui.text_edit_singleline(&mut skills).changed() {
+            latex_resume.skills = skills
+                .split(',')
+                .map(str::trim)
+                .filter(|skill| !skill.is_empty())
+                .map(str::to_string)
+                .collect();
+        }
+
+        ui.separator();
+        ui.heading(egui::RichText::new("Projects").color(egui::Color32::from_rgb(255, 69, 0)));
+        let mut project_to_remove = None;
+        for (idx, project) in latex_resume.projects.iter_mut().enumerate() {
+            ui.push_id(project.id.clone(), |ui| {
+                ui.group(|ui| {
+                    ui.horizontal(|ui| {
+                        ui.text_edit_singleline(&mut project.name);
+                        if ui.button("🗑").clicked() {
+                            project_to_remove = Some(idx);
+                        }
+                    });
+                    ui.horizontal(|ui| { ui.label("Link:"); ui.text_edit_singleline(&mut project.link); });
+                    for desc in &mut project.description {
+                        ui.text_edit_multiline(desc);
+                    }
+                    if ui.button("+ Bullet").clicked() {
+                        project.description.push(String::new());
+                    }
+                });
+            });
+        }
+        if let Some(idx) = project_to_remove {
+            latex_resume.projects.remove(idx);
+        }
+        if ui.button("+ Project").clicked() {
+            latex_resume.projects.push(ProjectItem {
+                id: uuid::Uuid::new_v4().to_string(),
+                name: "New Project".to_string(),
+                link: String::new(),
+                description: vec![],
+            });
+        }
     });
 }
 
+/// Drag payload carrying the index of the section being moved.
+struct SectionDrag(usize);
+
+/// Drag payload carrying the position of the item being moved.
+struct ItemDrag {
+    section: usize,
+    item: usize,
+}
+
+/// Highlights `response` while a `P` payload hovers it and returns the payload once dropped.
+///
+/// Payloads of other types are left in place so an outer drop target can still claim them.
+fn drop_payload<P: Send + Sync + 'static>(ui: &egui::Ui, response: &egui::Response) -> Option<std::sync::Arc<P>> {
+    if !egui::DragAndDrop::has_payload_of_type::<P>(ui.ctx()) {
+        return None;
+    }
+    if response.dnd_hover_payload::<P>().is_some() {
+        ui.painter().rect_stroke(response.rect, 2.0, ui.visuals().selection.stroke, egui::StrokeKind::Outside);
+    }
+    response.dnd_release_payload::<P>()
+}
+
+fn export_pdf(latex_resume: &LatexResume, latex_config: &LatexConfig, log_fn: &mut impl FnMut(&str)) {
+    let Some(path) = rfd::FileDialog::new().add_filter("pdf", &["pdf"]).set_file_name("resume.pdf").save_file() else {
+        return;
+    };
+    log_fn(&format!("Compiling with {:?}...", latex_config.engine));
+    match LatexManager::export_pdf(
+        latex_config.engine,
+        latex_config.resolved_binary(),
+        &latex_resume.generate_latex(),
+        &path,
+    ) {
+        Ok(pdf) => log_fn(&format!("PDF exported to {}", pdf.display())),
+        Err(e) => log_fn(&e.to_string()),
+    }
+}
+
 fn render_preview_panel(ui: &mut egui::Ui, latex_resume: &LatexResume) {
     ui.heading(egui::RichText::new("Live Preview").color(egui::Color32::from_rgb(255, 69, 0)));
     ui.separator();
@@ -149,6 +288,28 @@ fn render_preview_panel(ui: &mut egui::Ui, latex_resume: &LatexResume) {
                 }
                 ui.add_space(10.0);
             }
+
+            if !latex_resume.skills.is_empty() {
+                ui.heading(egui::RichText::new("Skills").size(16.0).strong().underline());
+                ui.label(latex_resume.skills.join(", "));
+                ui.add_space(10.0);
+            }
+
+            if !latex_resume.projects.is_empty() {
+                ui.heading(egui::RichText::new("Projects").size(16.0).strong().underline());
+                for project in &latex_resume.projects {
+                    ui.horizontal(|ui| {
+                        ui.label(egui::RichText::new(&project.name).strong());
+                        ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                            ui.label(egui::RichText::new(&project.link).monospace());
+                        });
+                    });
+                    for desc in &project.description {
+                        ui.label(format!("• {}", desc));
+                    }
+                    ui.add_space(5.0);
+                }
+            }
         });
     });
 }
@@ -156,7 +317,7 @@ fn render_preview_panel(ui: &mut egui::Ui, latex_resume: &LatexResume) {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use superpoweredcv::latex::{ResumeSection, SectionItem};
+    use crate::latex::{ResumeSection, SectionItem};
 
     #[test]
     fn test_latex_resume_structure() {
@@ -1,11 +1,13 @@
 use eframe::egui;
 use rfd::FileDialog;
 use std::path::PathBuf;
+use std::rc::Rc;
+use std::sync::mpsc;
 use crate::attacks::{InjectionPosition, Intensity};
 use crate::attacks::templates::GenerationType;
-use crate::llm::LlmClient;
+use crate::llm::{LlmClient, LlmUsage};
 use crate::config::AppConfig;
-use crate::gui::types::{InputSource, InjectionConfigGui, InjectionTypeGui, ProfileMask};
+use crate::gui::types::{InputSource, InjectionConfigGui, InjectionTypeGui, LlmUpdate, ProfileMask};
 use crate::generator::ScrapedProfile;
 
 /// Renders the main content area of the application.
@@ -28,10 +30,14 @@ use crate::generator::ScrapedProfile;
 /// * `show_log_window` - Toggle for the log window.
 /// * `show_injection_preview` - Toggle for the injection preview window.
 /// * `log_fn` - Callback for logging messages.
+/// * `generating` - Whether a generation worker is currently running.
 /// * `generate_fn` - Callback for triggering the generation process.
+/// * `cancel_fn` - Callback for cancelling the running generation.
 /// * `loaded_profile` - The currently loaded profile (if any).
 /// * `profile_mask` - The mask for enabling/disabling profile sections.
+/// * `llm_usage` - Tokens spent on LLM generations this session.
 /// * `update_history_fn` - Callback to update history.
+/// * `update_preset_history_fn` - Callback to record a saved or loaded preset path.
 pub fn render_main_content(
     ui: &mut egui::Ui,
     input_source: &mut InputSource,
@@ -45,10 +51,14 @@ pub fn render_main_content(
     show_ai_assistant: &mut bool,
     show_ats_dashboard: &mut bool,
     mut log_fn: impl FnMut(&str),
+    generating: bool,
     mut generate_fn: impl FnMut(),
+    mut cancel_fn: impl FnMut(),
     loaded_profile: &mut Option<ScrapedProfile>,
     profile_mask: &mut ProfileMask,
+    llm_usage: &mut LlmUsage,
     mut update_history_fn: impl FnMut(String),
+    mut update_preset_history_fn: impl FnMut(String),
 ) {
     egui::ScrollArea::vertical().show(ui, |ui| {
         ui.vertical_centered(|ui| {
@@ -78,6 +88,9 @@ pub fn render_main_content(
         }
         ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
             ui.label(egui::RichText::new("v1.0.0-alpha").weak().small());
+            if llm_usage.requests > 0 {
+                ui.label(egui::RichText::new(format!("LLM: {}", llm_usage)).weak().small());
+            }
         });
     });
     ui.add_space(10.0);
@@ -235,6 +248,8 @@ pub fn render_main_content(
     ui.add_space(10.0);
 
     // Injection Modules
+    let mut preset_to_load = None;
+    let mut preset_log = None;
     ui.group(|ui| {
         ui.set_width(ui.available_width());
         ui.horizontal(|ui| {
@@ -243,6 +258,30 @@ pub fn render_main_content(
                 if ui.button("✚ ADD MODULE").clicked() {
                     injections.push(InjectionConfigGui::default());
                 }
+                if ui.button("💾 SAVE PRESET").clicked() {
+                    let picked = FileDialog::new().add_filter("json", &["json"]).set_file_name("preset.json").save_file();
+                    if let Some(path) = picked {
+                        match InjectionConfigGui::save_preset(&path, injections) {
+                            Ok(()) => {
+                                preset_log = Some(format!("PRESET: SAVED {} MODULE(S)", injections.len()));
+                                update_preset_history_fn(path.to_string_lossy().to_string());
+                            }
+                            Err(e) => preset_log = Some(format!("Error saving preset: {}", e)),
+                        }
+                    }
+                }
+                if ui.button("📂 LOAD PRESET").clicked() {
+                    preset_to_load = FileDialog::new().add_filter("json", &["json"]).pick_file();
+                }
+                egui::ComboBox::from_id_salt("recent_presets")
+                    .selected_text("Recent Presets...")
+                    .show_ui(ui, |ui| {
+                        for recent in &config.history.recent_presets {
+                            if ui.selectable_label(false, recent).clicked() {
+                                preset_to_load = Some(PathBuf::from(recent));
+                            }
+                        }
+                    });
                 if ui.button("👁 PREVIEW").clicked() {
                     *show_injection_preview = !*show_injection_preview;
                 }
@@ -323,11 +362,32 @@ pub fn render_main_content(
                                 ui.text_edit_multiline(&mut injection.job_description);
                             }
 
+                            while let Some(rx) = injection.pending_llm.clone() {
+                                match rx.try_recv() {
+                                    Ok(LlmUpdate::Token(token)) => injection.current_phrase.push_str(&token),
+                                    Ok(LlmUpdate::Done(Ok(response))) => {
+                                        llm_usage.record(&response);
+                                        injection.phrases.push(response.text);
+                                        injection.current_phrase.clear();
+                                        injection.pending_llm = None;
+                                    }
+                                    Ok(LlmUpdate::Done(Err(e))) => {
+                                        pending_error = Some(format!("LLM Error: {}", e));
+                                        injection.pending_llm = None;
+                                    }
+                                    Err(mpsc::TryRecvError::Empty) => break,
+                                    Err(mpsc::TryRecvError::Disconnected) => injection.pending_llm = None,
+                                }
+                            }
+
                             if injection.generation_type != GenerationType::Static {
-                                if ui.button("GENERATE CONTENT (LLM)").clicked() {
-                                    // Need to handle async or blocking call here. 
-                                    // For now, we clone config and do it blocking (freezes UI briefly)
-                                    let client = LlmClient::new(config.llm.clone());
+                                if injection.pending_llm.is_some() {
+                                    ui.horizontal(|ui| {
+                                        ui.spinner();
+                                        ui.label("Generating content...");
+                                    });
+                                } else if ui.button("GENERATE CONTENT (LLM)").clicked() {
+                                    let client = LlmClient::with_network(config.llm.clone(), &config.network);
                                     let prompt = match injection.generation_type {
                                         GenerationType::LlmControl => &config.prompts.control_sequence_generation,
                                         GenerationType::Pollution => &config.prompts.pollution_skills_generation,
@@ -339,11 +399,20 @@ pub fn render_main_content(
                                     } else {
                                         prompt.to_string()
                                     };
-                                    
-                                    match client.generate(&final_prompt) {
-                                        Ok(c) => injection.phrases.push(c),
-                                        Err(e) => pending_error = Some(format!("LLM Error: {}", e)),
-                                    }
+
+                                    let (tx, rx) = mpsc::channel();
+                                    let ctx = ui.ctx().clone();
+                                    // Tokens land in the phrase field as they stream in.
+                                    injection.current_phrase.clear();
+                                    std::thread::spawn(move || {
+                                        let result = client.generate_streaming(&final_prompt, |token| {
+                                            let _ = tx.send(LlmUpdate::Token(token.to_string()));
+                                            ctx.request_repaint();
+                                        });
+                                        let _ = tx.send(LlmUpdate::Done(result.map_err(|e| e.to_string())));
+                                        ctx.request_repaint();
+                                    });
+                                    injection.pending_llm = Some(Rc::new(rx));
                                 }
                             }
 
@@ -372,6 +441,20 @@ pub fn render_main_content(
         });
     });
 
+    if let Some(path) = preset_to_load {
+        match InjectionConfigGui::load_preset(&path) {
+            Ok(modules) => {
+                preset_log = Some(format!("PRESET: LOADED {} MODULE(S)", modules.len()));
+                *injections = modules;
+                update_preset_history_fn(path.to_string_lossy().to_string());
+            }
+            Err(e) => preset_log = Some(format!("Error loading preset: {}", e)),
+        }
+    }
+    if let Some(msg) = preset_log {
+        log_fn(&msg);
+    }
+
     ui.add_space(20.0);
 
     // Action Button
@@ -380,7 +463,16 @@ pub fn render_main_content(
             .fill(egui::Color32::from_rgb(255, 69, 0))
             .min_size(egui::vec2(200.0, 50.0));
         
-        if ui.add(btn).clicked() {
+        if generating {
+            ui.add_enabled(false, btn);
+            ui.horizontal(|ui| {
+                ui.spinner();
+                ui.label(egui::RichText::new("GENERATING...").monospace());
+                if ui.button("CANCEL").clicked() {
+                    cancel_fn();
+                }
+            });
+        } else if ui.add(btn).clicked() {
             generate_fn();
         }
     });
@@ -1,13 +1,23 @@
+use std::sync::{mpsc, Arc, Mutex};
 use eframe::egui;
-use crate::config::AppConfig;
+use crate::config::{AppConfig, LatexEngine, LlmApi};
 use crate::latex::manager::LatexManager;
+use crate::llm::{self, LocalModel, LocalModelScan};
 use crate::gui::types::LlmProvider;
+use crate::gui::styles::setup_custom_styles;
+
+/// How long to wait on each local inference server before reporting it unavailable.
+const LOCAL_MODEL_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(2);
+
+/// In-flight local model scan; the worker sends one [`LocalModelScan`] when both servers have answered or timed out.
+type PendingScan = Arc<Mutex<mpsc::Receiver<LocalModelScan>>>;
 
 #[derive(PartialEq, Clone, Copy)]
 enum SettingsTab {
     Llm,
     Prompts,
     Latex,
+    Network,
     General,
 }
 
@@ -26,6 +36,7 @@ pub fn render_settings(ui: &mut egui::Ui, config: &mut AppConfig, selected_provi
         ui.selectable_value(&mut current_tab, SettingsTab::Llm, "LLM Provider");
         ui.selectable_value(&mut current_tab, SettingsTab::Prompts, "Prompts");
         ui.selectable_value(&mut current_tab, SettingsTab::Latex, "LaTeX");
+        ui.selectable_value(&mut current_tab, SettingsTab::Network, "Network");
         ui.selectable_value(&mut current_tab, SettingsTab::General, "General");
     });
     ui.separator();
@@ -34,26 +45,53 @@ pub fn render_settings(ui: &mut egui::Ui, config: &mut AppConfig, selected_provi
 
     egui::ScrollArea::vertical().show(ui, |ui| {
         match current_tab {
-            SettingsTab::Llm => render_llm_settings(ui, config, selected_provider),
+            SettingsTab::Llm => render_llm_settings(ui, config, selected_provider, &mut log_fn),
             SettingsTab::Prompts => render_prompt_settings(ui, config),
             SettingsTab::Latex => render_latex_settings(ui, config, &mut log_fn),
+            SettingsTab::Network => render_network_settings(ui, config),
             SettingsTab::General => render_general_settings(ui, config),
         }
 
         ui.add_space(20.0);
-        if ui.button("Save Configuration").clicked() {
+        let confirm_id = egui::Id::new("settings_confirm_overwrite");
+        let mut confirm_overwrite = ui.data(|d| d.get_temp::<bool>(confirm_id)).unwrap_or(false);
+        let mut do_save = false;
+
+        if confirm_overwrite {
+            ui.label(egui::RichText::new("config.json was modified outside this window.").color(egui::Color32::YELLOW));
+            ui.horizontal(|ui| {
+                if ui.button("Overwrite").clicked() {
+                    do_save = true;
+                    confirm_overwrite = false;
+                }
+                if ui.button("Reload From Disk").clicked() {
+                    *config = AppConfig::load();
+                    confirm_overwrite = false;
+                    log_fn("Configuration reloaded from disk.");
+                }
+            });
+        } else if ui.button("Save Configuration").clicked() {
+            if config.modified_externally() {
+                confirm_overwrite = true;
+            } else {
+                do_save = true;
+            }
+        }
+
+        if do_save {
             if let Err(e) = config.save() {
                 log_fn(&format!("Config Save Error: {}", e));
             } else {
                 log_fn("Configuration Saved.");
             }
         }
+        ui.data_mut(|d| d.insert_temp(confirm_id, confirm_overwrite));
     });
 }
 
 
 
-fn render_llm_settings(ui: &mut egui::Ui, config: &mut AppConfig, selected_provider: &mut LlmProvider) {
+fn render_llm_settings(ui: &mut egui::Ui, config: &mut AppConfig, selected_provider: &mut LlmProvider, log_fn: &mut impl FnMut(&str)) {
     ui.heading(egui::RichText::new("LLM Provider Settings").color(egui::Color32::from_rgb(255, 69, 0)));
     ui.add_space(10.0);
 
@@ -111,7 +149,7 @@ fn render_llm_settings(ui: &mut egui::Ui, config: &mut AppConfig, selected_provi
                             config.llm.model = "local-model".to_string();
                         }
                         LlmProvider::Gemini => {
-                            config.llm.api_base_url = "https://generativelanguage.googleapis.com/v1beta/openai".to_string();
+                            config.llm.api_base_url = "https://generativelanguage.googleapis.com/v1beta".to_string();
                             config.llm.model = "gemini-1.5-pro-latest".to_string();
                         }
                         LlmProvider::Cohere => {
@@ -130,17 +168,151 @@ fn render_llm_settings(ui: &mut egui::Ui, config: &mut AppConfig, selected_provi
             });
     });
 
+    ui.add_space(5.0);
+    let detected_id = egui::Id::new("detected_local_models");
+    let mut detected = ui.data(|d| d.get_temp::<Vec<LocalModel>>(detected_id)).unwrap_or_default();
+    let scan_id = egui::Id::new("local_model_scan");
+    let mut pending_scan = ui.data(|d| d.get_temp::<PendingScan>(scan_id));
+    if let Some(rx) = &pending_scan {
+        let received = match rx.lock() {
+            Ok(rx) => rx.try_recv(),
+            Err(_) => Err(mpsc::TryRecvError::Disconnected),
+        };
+        match received {
+            Ok(scan) => {
+                for error in &scan.errors {
+                    log_fn(error);
+                }
+                log_fn(&format!("Detected {} local model(s).", scan.models.len()));
+                detected = scan.models;
+                pending_scan = None;
+            }
+            Err(mpsc::TryRecvError::Empty) => {}
+            Err(mpsc::TryRecvError::Disconnected) => pending_scan = None,
+        }
+    }
+    ui.horizontal(|ui| {
+        if pending_scan.is_some() {
+            ui.spinner();
+            ui.label("Detecting local models...");
+        } else if ui.button("Auto-Detect Local Models").clicked() {
+            // Each server may take the full timeout, so the scan runs off the UI thread.
+            let (tx, rx) = mpsc::channel();
+            let ctx = ui.ctx().clone();
+            std::thread::spawn(move || {
+                let _ = tx.send(llm::detect_local_models(llm::OLLAMA_URL, llm::LM_STUDIO_URL, LOCAL_MODEL_TIMEOUT));
+                ctx.request_repaint();
+            });
+            pending_scan = Some(Arc::new(Mutex::new(rx)));
+        }
+
+        if !detected.is_empty() {
+            egui::ComboBox::from_id_salt("local_models")
+                .selected_text("Detected Models...")
+                .show_ui(ui, |ui| {
+                    for model in &detected {
+                        let label = format!("{} ({})", model.name, model.api_base_url);
+                        if ui.selectable_label(config.llm.model == model.name, label).clicked() {
+                            config.llm.model = model.name.clone();
+                            config.llm.api_base_url = model.api_base_url.clone();
+                            *selected_provider = if model.api_base_url.starts_with(llm::OLLAMA_URL) {
+                                LlmProvider::Ollama
+                            } else {
+                                LlmProvider::LMStudio
+                            };
+                        }
+                    }
+                });
+        }
+    });
+    ui.data_mut(|d| {
+        d.insert_temp(detected_id, detected);
+        match pending_scan {
+            Some(rx) => d.insert_temp(scan_id, rx),
+            None => d.remove::<PendingScan>(scan_id),
+        }
+    });
+
     ui.label("API Base URL:");
     ui.text_edit_singleline(&mut config.llm.api_base_url);
 
     ui.label("Model Name:");
     ui.text_edit_singleline(&mut config.llm.model);
 
+    ui.horizontal(|ui| {
+        ui.label("API Format:");
+        egui::ComboBox::from_id_salt("llm_api")
+            .selected_text(format!("{:?}", config.llm.api))
+            .show_ui(ui, |ui| {
+                ui.selectable_value(&mut config.llm.api, LlmApi::Auto, "Auto");
+                ui.selectable_value(&mut config.llm.api, LlmApi::OpenAiCompatible, "OpenAI-compatible");
+                ui.selectable_value(&mut config.llm.api, LlmApi::Anthropic, "Anthropic Messages");
+                ui.selectable_value(&mut config.llm.api, LlmApi::Gemini, "Gemini generateContent");
+            });
+    });
+
     ui.label("API Key (Optional):");
     let mut api_key = config.llm.api_key.clone().unwrap_or_default();
     if ui.add(egui::TextEdit::singleline(&mut api_key).password(true)).changed() {
         config.llm.api_key = if api_key.is_empty() { None } else { Some(api_key) };
     }
+
+    // Querying the keyring can be slow, so its status is only refreshed on changes.
+    let keyring_id = egui::Id::new("keyring_key_stored");
+    let mut key_stored = match ui.data(|d| d.get_temp::<bool>(keyring_id)) {
+        Some(stored) => stored,
+        None => config.llm.api_key_in_keyring && config.llm.keyring_api_key().is_some(),
+    };
+    ui.horizontal(|ui| {
+        if key_stored {
+            ui.label(egui::RichText::new(format!("Key stored in OS keyring ({})", config.llm.provider_name())).color(egui::Color32::GREEN));
+            if ui.button("Clear").clicked() {
+                match config.llm.clear_keyring_api_key() {
+                    Ok(()) => {
+                        key_stored = false;
+                        log_fn("API key removed from the OS keyring.");
+                    }
+                    Err(e) => log_fn(&format!("Keyring Error: {}", e)),
+                }
+            }
+        } else {
+            ui.label("No key in OS keyring");
+        }
+        if ui.add_enabled(config.llm.api_key.is_some(), egui::Button::new("Store in Keyring")).clicked() {
+            match config.llm.store_api_key_in_keyring() {
+                Ok(()) => {
+                    key_stored = true;
+                    log_fn("API key moved to the OS keyring. Save the configuration to drop it from config.json.");
+                }
+                Err(e) => log_fn(&format!("Keyring Error: {}", e)),
+            }
+        }
+    });
+    ui.data_mut(|d| d.insert_temp(keyring_id, key_stored));
+
+    ui.horizontal(|ui| {
+        ui.label("Temperature:");
+        ui.add(egui::Slider::new(&mut config.llm.temperature, 0.0..=2.0).step_by(0.05));
+    });
+
+    ui.horizontal(|ui| {
+        ui.label("Max Tokens:");
+        ui.add(egui::DragValue::new(&mut config.llm.max_tokens).range(1..=32768));
+    });
+
+    ui.label("System Prompt (Optional):");
+    ui.text_edit_multiline(&mut config.llm.system_prompt);
+
+    ui.add_space(10.0);
+    ui.horizontal(|ui| {
+        ui.checkbox(&mut config.llm.cache_enabled, "Cache LLM responses");
+        if ui.button("Clear LLM Cache").clicked() {
+            match llm::clear_cache(&config.llm.response_cache_dir()) {
+                Ok(removed) => log_fn(&format!("Cleared {} cached LLM response(s).", removed)),
+                Err(e) => log_fn(&format!("Cache Error: {}", e)),
+            }
+        }
+    });
 }
 
 fn render_prompt_settings(ui: &mut egui::Ui, config: &mut AppConfig) {
@@ -156,11 +328,41 @@ fn render_prompt_settings(ui: &mut egui::Ui, config: &mut AppConfig) {
     ui.text_edit_multiline(&mut config.prompts.ad_targeted_pollution);
 }
 
+fn render_network_settings(ui: &mut egui::Ui, config: &mut AppConfig) {
+    ui.heading(egui::RichText::new("Network").color(egui::Color32::from_rgb(255, 69, 0)));
+    ui.add_space(10.0);
+
+    ui.label("Proxy URL (Optional):");
+    let mut proxy = config.network.proxy.clone().unwrap_or_default();
+    if ui.add(egui::TextEdit::singleline(&mut proxy).hint_text("http://proxy.example:8080")).changed() {
+        config.network.proxy = if proxy.trim().is_empty() { None } else { Some(proxy) };
+    }
+    if config.network.client_builder().is_err() {
+        ui.label(egui::RichText::new("Invalid proxy URL; requests will ignore it.").color(egui::Color32::YELLOW));
+    }
+    ui.label("Applies to LLM and ATS endpoint requests. When empty, HTTPS_PROXY, HTTP_PROXY and NO_PROXY are used.");
+}
+
 fn render_latex_settings(ui: &mut egui::Ui, config: &mut AppConfig, log_fn: &mut impl FnMut(&str)) {
     ui.heading(egui::RichText::new("LaTeX Environment").color(egui::Color32::from_rgb(255, 69, 0)));
     ui.add_space(10.0);
 
     ui.checkbox(&mut config.latex.auto_detect, "Auto-detect System LaTeX");
+
+    ui.horizontal(|ui| {
+        ui.label("Engine:");
+        let previous = config.latex.engine;
+        egui::ComboBox::from_id_salt("latex_engine")
+            .selected_text(format!("{:?}", config.latex.engine))
+            .show_ui(ui, |ui| {
+                for engine in LatexEngine::ALL {
+                    ui.selectable_value(&mut config.latex.engine, engine, format!("{:?}", engine));
+                }
+            });
+        if config.latex.engine != previous {
+            config.latex.binary_path = config.latex.engine.default_binary().to_string();
+        }
+    });
     
     ui.horizontal(|ui| {
         ui.label("Binary Path:");
@@ -170,6 +372,9 @@ fn render_latex_settings(ui: &mut egui::Ui, config: &mut AppConfig, log_fn: &mut
     if config.latex.auto_detect {
         if ui.button("Run Auto-Detection").clicked() {
             if let Some(path) = LatexManager::auto_detect() {
+                if let Some(engine) = LatexEngine::from_binary(&path) {
+                    config.latex.engine = engine;
+                }
                 config.latex.binary_path = path;
                 log_fn("LaTeX binary detected.");
             } else {
@@ -180,7 +385,7 @@ fn render_latex_settings(ui: &mut egui::Ui, config: &mut AppConfig, log_fn: &mut
 
     ui.add_space(10.0);
     ui.label("Status:");
-    if LatexManager::check_binary(&config.latex.binary_path) {
+    if LatexManager::check_engine(config.latex.engine, config.latex.resolved_binary()) {
         ui.label(egui::RichText::new("● READY").color(egui::Color32::GREEN));
     } else {
         ui.label(egui::RichText::new("● NOT FOUND").color(egui::Color32::RED));
@@ -214,8 +419,49 @@ fn render_general_settings(ui: &mut egui::Ui, config: &mut AppConfig) {
         config.history.recent_json_files.remove(idx);
     }
 
+    ui.add_space(10.0);
+    ui.label("Recent Injection Presets:");
+
+    let mut preset_to_remove = None;
+    for (idx, file) in config.history.recent_presets.iter().enumerate() {
+        ui.horizontal(|ui| {
+            ui.label(format!("{}. {}", idx + 1, file));
+            if ui.button("🗑").clicked() {
+                preset_to_remove = Some(idx);
+            }
+        });
+    }
+
+    if let Some(idx) = preset_to_remove {
+        config.history.recent_presets.remove(idx);
+    }
+
     if ui.button("Clear All History").clicked() {
         config.history.recent_json_files.clear();
+        config.history.recent_presets.clear();
+    }
+
+    ui.add_space(10.0);
+    ui.label("Theme:");
+    let mut theme_changed = false;
+    ui.horizontal(|ui| {
+        ui.label("Accent Color:");
+        theme_changed |= ui.color_edit_button_srgb(&mut config.theme.accent_rgb).changed();
+    });
+    ui.horizontal(|ui| {
+        ui.label("Background Color:");
+        theme_changed |= ui.color_edit_button_srgb(&mut config.theme.background_rgb).changed();
+    });
+    ui.horizontal(|ui| {
+        ui.label("Corner Radius:");
+        theme_changed |= ui.add(egui::DragValue::new(&mut config.theme.corner_radius).range(0..=16)).changed();
+    });
+    if ui.button("Reset Theme").clicked() {
+        config.theme = Default::default();
+        theme_changed = true;
+    }
+    if theme_changed {
+        setup_custom_styles(ui.ctx(), &config.theme);
     }
 }
 
@@ -1,4 +1,5 @@
 use eframe::egui;
+use crate::config::ThemeConfig;
 
 pub fn setup_custom_fonts(ctx: &egui::Context) {
     let mut fonts = egui::FontDefinitions::default();
@@ -8,17 +9,19 @@ pub fn setup_custom_fonts(ctx: &egui::Context) {
     ctx.set_fonts(fonts);
 }
 
-pub fn setup_custom_styles(ctx: &egui::Context) {
+pub fn setup_custom_styles(ctx: &egui::Context, theme: &ThemeConfig) {
     let mut visuals = egui::Visuals::dark();
     // Brutalist Palette
-    let bg_color = egui::Color32::from_rgb(10, 10, 10);
+    let [bg_r, bg_g, bg_b] = theme.background_rgb;
+    let [accent_r, accent_g, accent_b] = theme.accent_rgb;
+    let bg_color = egui::Color32::from_rgb(bg_r, bg_g, bg_b);
     let fg_color = egui::Color32::from_rgb(255, 255, 255);
-    let accent_color = egui::Color32::from_rgb(255, 69, 0); // Red-Orange (Fireish)
+    let accent_color = egui::Color32::from_rgb(accent_r, accent_g, accent_b);
     let border_color = egui::Color32::from_rgb(255, 255, 255); // White borders for brutalist look
 
     visuals.window_fill = bg_color;
     visuals.panel_fill = bg_color;
-    visuals.window_corner_radius = egui::CornerRadius::ZERO;
+    visuals.window_corner_radius = egui::CornerRadius::same(theme.corner_radius);
     visuals.window_stroke = egui::Stroke::new(2.0, border_color);
     
     visuals.widgets.noninteractive.bg_stroke = egui::Stroke::new(2.0, border_color);
@@ -165,3 +168,19 @@ fn title_bar_ui(
         });
     });
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_setup_custom_styles_applies_accent() {
+        let ctx = egui::Context::default();
+        let theme = ThemeConfig {
+            accent_rgb: [0, 128, 255],
+            ..ThemeConfig::default()
+        };
+        setup_custom_styles(&ctx, &theme);
+        assert_eq!(ctx.style().visuals.widgets.active.bg_fill, egui::Color32::from_rgb(0, 128, 255));
+    }
+}
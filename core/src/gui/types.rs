@@ -1,6 +1,13 @@
-use std::path::PathBuf;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::sync::mpsc;
 use crate::attacks::{InjectionPosition, Intensity};
 use crate::attacks::templates::GenerationType;
+pub use crate::generator::ProfileMask;
+use crate::llm::LlmResponse;
+use serde::{Deserialize, Serialize};
 
 #[derive(PartialEq, Clone)]
 pub enum InputSource {
@@ -25,15 +32,27 @@ pub enum LlmProvider {
     Custom,
 }
 
-#[derive(Clone)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct InjectionConfigGui {
     pub injection_type: InjectionTypeGui,
     pub intensity: Intensity,
     pub position: InjectionPosition,
     pub phrases: Vec<String>,
+    #[serde(skip)]
     pub current_phrase: String,
     pub generation_type: GenerationType,
     pub job_description: String,
+    /// In-flight LLM request for this module; the worker streams tokens, then the generated phrase or an error.
+    #[serde(skip)]
+    pub pending_llm: Option<Rc<mpsc::Receiver<LlmUpdate>>>,
+}
+
+/// Progress of a streaming LLM generation sent from its worker thread.
+pub enum LlmUpdate {
+    /// A chunk of the completion, in arrival order.
+    Token(String),
+    /// The finished completion, or the error that ended it.
+    Done(Result<LlmResponse, String>),
 }
 
 impl Default for InjectionConfigGui {
@@ -46,11 +65,26 @@ impl Default for InjectionConfigGui {
             current_phrase: String::new(),
             generation_type: GenerationType::Static,
             job_description: String::new(),
+            pending_llm: None,
         }
     }
 }
 
-#[derive(PartialEq, Clone, Copy, Debug)]
+impl InjectionConfigGui {
+    /// Writes a set of injection modules to a JSON preset file.
+    pub fn save_preset(path: &Path, modules: &[InjectionConfigGui]) -> io::Result<()> {
+        let content = serde_json::to_string_pretty(modules)?;
+        fs::write(path, content)
+    }
+
+    /// Reads injection modules back from a preset written by [`InjectionConfigGui::save_preset`].
+    pub fn load_preset(path: &Path) -> io::Result<Vec<InjectionConfigGui>> {
+        let content = fs::read_to_string(path)?;
+        Ok(serde_json::from_str(&content)?)
+    }
+}
+
+#[derive(PartialEq, Clone, Copy, Debug, Serialize, Deserialize)]
 pub enum InjectionTypeGui {
     VisibleMetaBlock,
     LowVisibilityBlock,
@@ -62,10 +96,3 @@ pub enum InjectionTypeGui {
     TrackingPixel,
     CodeInjection,
 }
-
-#[derive(Default, Clone)]
-pub struct ProfileMask {
-    pub experience_enabled: Vec<bool>,
-    pub education_enabled: Vec<bool>,
-    pub skills_enabled: Vec<bool>,
-}
@@ -8,6 +8,13 @@ pub struct LatexResume {
     pub sections: Vec<ResumeSection>,
     pub template: LatexTemplate,
     pub font: String,
+    #[serde(default)]
+    pub skills: Vec<String>,
+    #[serde(default)]
+    pub projects: Vec<ProjectItem>,
+    /// Order in which the blocks are emitted; empty means [`ResumeBlock::DEFAULT_ORDER`].
+    #[serde(default)]
+    pub block_order: Vec<ResumeBlock>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
@@ -35,6 +42,27 @@ pub struct SectionItem {
     pub description: Vec<String>,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProjectItem {
+    pub id: String, // For DnD
+    pub name: String,
+    pub link: String,
+    pub description: Vec<String>,
+}
+
+/// Top-level blocks of the generated document.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum ResumeBlock {
+    /// The free-form `sections`, in their own order.
+    Sections,
+    Skills,
+    Projects,
+}
+
+impl ResumeBlock {
+    pub const DEFAULT_ORDER: [ResumeBlock; 3] = [ResumeBlock::Sections, ResumeBlock::Skills, ResumeBlock::Projects];
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub enum LatexTemplate {
     Modern,
@@ -99,18 +127,18 @@ impl LatexResume {
         }
 
         // Skills
-        if !profile.skills.is_empty() {
-            self.sections.push(ResumeSection {
-                id: uuid::Uuid::new_v4().to_string(),
-                title: "Skills".to_string(),
-                items: vec![SectionItem {
-                    id: uuid::Uuid::new_v4().to_string(),
-                    title: "Technical Skills".to_string(),
-                    subtitle: "".to_string(),
-                    date: "".to_string(),
-                    description: profile.skills.clone(),
-                }],
-            });
+        self.skills = profile.skills.clone();
+    }
+
+    /// Moves the section at `from` so it ends up at index `to`; out-of-range indices are ignored.
+    pub fn move_section(&mut self, from: usize, to: usize) {
+        move_element(&mut self.sections, from, to);
+    }
+
+    /// Moves an item within a single section, leaving other sections untouched.
+    pub fn move_item(&mut self, section: usize, from: usize, to: usize) {
+        if let Some(section) = self.sections.get_mut(section) {
+            move_element(&mut section.items, from, to);
         }
     }
 
@@ -143,37 +171,111 @@ impl LatexResume {
 \end{{center}}
 \vspace{{10pt}}
 ", 
-            self.personal_info.name,
-            self.personal_info.email,
-            self.personal_info.phone,
-            self.personal_info.linkedin,
-            self.personal_info.github
+            escape_latex(&self.personal_info.name),
+            escape_latex(&self.personal_info.email),
+            escape_latex(&self.personal_info.phone),
+            escape_latex(&self.personal_info.linkedin),
+            escape_latex(&self.personal_info.github)
         ));
 
-        // Sections
+        let order = if self.block_order.is_empty() { ResumeBlock::DEFAULT_ORDER.to_vec() } else { self.block_order.clone() };
+        for block in order {
+            match block {
+                ResumeBlock::Sections => self.push_sections(&mut latex),
+                ResumeBlock::Skills => self.push_skills(&mut latex),
+                ResumeBlock::Projects => self.push_projects(&mut latex),
+            }
+        }
+
+        latex.push_str(r"\end{document}");
+        latex
+    }
+
+    fn push_sections(&self, latex: &mut String) {
         for section in &self.sections {
-            latex.push_str(&format!(r"\section*{{{}}}
-\hrule
-\vspace{{5pt}}
-", section.title.to_uppercase()));
+            push_section_header(latex, &section.title);
 
             for item in &section.items {
                 latex.push_str(&format!(r"\noindent \textbf{{{}}} \hfill {} \\
 \textit{{{}}}
-\begin{{itemize}}[noitemsep,topsep=0pt]
-", item.title, item.date, item.subtitle));
-
-                for desc in &item.description {
-                    latex.push_str(&format!(r"    \item {}
-", desc));
-                }
-                latex.push_str(r"\end{itemize}
-\vspace{5pt}
-");
+", escape_latex(&item.title), escape_latex(&item.date), escape_latex(&item.subtitle)));
+                push_itemize(latex, &item.description);
             }
         }
+    }
 
-        latex.push_str(r"\end{document}");
-        latex
+    fn push_skills(&self, latex: &mut String) {
+        if self.skills.is_empty() {
+            return;
+        }
+        push_section_header(latex, "Skills");
+        let skills: Vec<String> = self.skills.iter().map(|skill| escape_latex(skill)).collect();
+        latex.push_str(&format!(r"\noindent {}
+\vspace{{5pt}}
+", skills.join(", ")));
     }
+
+    fn push_projects(&self, latex: &mut String) {
+        if self.projects.is_empty() {
+            return;
+        }
+        push_section_header(latex, "Projects");
+        for project in &self.projects {
+            latex.push_str(&format!(r"\noindent \textbf{{{}}} \hfill \texttt{{{}}} \\
+", escape_latex(&project.name), escape_latex(&project.link)));
+            push_itemize(latex, &project.description);
+        }
+    }
+}
+
+fn move_element<T>(items: &mut Vec<T>, from: usize, to: usize) {
+    if from == to || from >= items.len() || to >= items.len() {
+        return;
+    }
+    let element = items.remove(from);
+    items.insert(to, element);
+}
+
+/// Escapes characters with special meaning in LaTeX so user-provided text
+/// is typeset literally.
+pub fn escape_latex(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\\' => escaped.push_str(r"\textbackslash{}"),
+            '~' => escaped.push_str(r"\textasciitilde{}"),
+            '^' => escaped.push_str(r"\textasciicircum{}"),
+            '&' | '%' | '$' | '#' | '_' | '{' | '}' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+fn push_section_header(latex: &mut String, title: &str) {
+    latex.push_str(&format!(r"\section*{{{}}}
+\hrule
+\vspace{{5pt}}
+", escape_latex(&title.to_uppercase())));
+}
+
+fn push_itemize(latex: &mut String, bullets: &[String]) {
+    // pdflatex rejects an itemize without any \item.
+    if bullets.is_empty() {
+        latex.push_str(r"\par\vspace{5pt}
+");
+        return;
+    }
+    latex.push_str(r"\begin{itemize}[noitemsep,topsep=0pt]
+");
+    for bullet in bullets {
+        latex.push_str(&format!(r"    \item {}
+", escape_latex(bullet)));
+    }
+    latex.push_str(r"\end{itemize}
+\vspace{5pt}
+");
 }
@@ -1,4 +1,8 @@
-use std::process::Command;
+use crate::config::LatexEngine;
+use crate::{AnalysisError, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
 
 /// Manages the LaTeX environment and binary detection.
 pub struct LatexManager;
@@ -35,6 +39,82 @@ impl LatexManager {
         }
         None
     }
+
+    /// Checks that `binary_path` runs and identifies itself as `engine` in
+    /// its `--version` banner (pdfTeX, XeTeX or Tectonic).
+    pub fn check_engine(engine: LatexEngine, binary_path: &str) -> bool {
+        let marker = match engine {
+            LatexEngine::Pdflatex => "pdftex",
+            LatexEngine::Xelatex => "xetex",
+            LatexEngine::Tectonic => "tectonic",
+        };
+        Command::new(binary_path)
+            .arg("--version")
+            .output()
+            .map(|output| {
+                output.status.success() && String::from_utf8_lossy(&output.stdout).to_lowercase().contains(marker)
+            })
+            .unwrap_or(false)
+    }
+
+    /// Builds the command compiling `tex_path` into a PDF inside `output_dir`.
+    ///
+    /// `pdflatex` and `xelatex` run non-interactively with `-output-directory`;
+    /// Tectonic compiles in a single pass and takes `--outdir` instead.
+    pub fn compile_command(engine: LatexEngine, binary_path: &str, tex_path: &Path, output_dir: &Path) -> Command {
+        let mut command = Command::new(binary_path);
+        match engine {
+            LatexEngine::Pdflatex | LatexEngine::Xelatex => {
+                command
+                    .arg("-interaction=nonstopmode")
+                    .arg("-halt-on-error")
+                    .arg(format!("-output-directory={}", output_dir.display()))
+                    .arg(tex_path);
+            }
+            LatexEngine::Tectonic => {
+                command.arg("--outdir").arg(output_dir).arg(tex_path);
+            }
+        }
+        command
+    }
+
+    /// Compiles `tex_path` with the given engine and returns the process output.
+    pub fn compile(engine: LatexEngine, binary_path: &str, tex_path: &Path, output_dir: &Path) -> std::io::Result<Output> {
+        Self::compile_command(engine, binary_path, tex_path, output_dir).output()
+    }
+
+    /// Writes `source` next to `pdf_path` as a `.tex` file and compiles it.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<PathBuf>` - The compiled PDF, or a `PdfError` carrying the
+    ///   first error reported by the engine.
+    pub fn export_pdf(engine: LatexEngine, binary_path: &str, source: &str, pdf_path: &Path) -> Result<PathBuf> {
+        let tex_path = pdf_path.with_extension("tex");
+        let output_dir = match pdf_path.parent() {
+            Some(dir) if !dir.as_os_str().is_empty() => dir.to_path_buf(),
+            _ => PathBuf::from("."),
+        };
+        fs::write(&tex_path, source)?;
+
+        let output = Self::compile(engine, binary_path, &tex_path, &output_dir)?;
+        let compiled = pdf_path.with_extension("pdf");
+        if output.status.success() && compiled.exists() {
+            return Ok(compiled);
+        }
+        let log = format!("{}\n{}", String::from_utf8_lossy(&output.stdout), String::from_utf8_lossy(&output.stderr));
+        let error = Self::first_error(&log).unwrap_or_else(|| format!("{} exited with {}", binary_path, output.status));
+        Err(AnalysisError::PdfError(format!("LaTeX compilation failed: {}", error)))
+    }
+
+    /// Extracts the first error from compiler output: a `! ` line as printed
+    /// by pdflatex/xelatex, or an `error:` line as printed by Tectonic.
+    pub fn first_error(log: &str) -> Option<String> {
+        log.lines()
+            .map(str::trim)
+            .find(|line| line.starts_with("! ") || line.starts_with("error:"))
+            .map(|line| line.trim_start_matches("! ").to_string())
+    }
 }
 
 #[cfg(test)]
@@ -1,5 +1,6 @@
 pub mod pipeline;
 pub mod pdf;
+pub mod docx;
 pub mod pdf_utils;
 pub mod profile;
 pub mod analysis;
@@ -12,6 +13,7 @@ pub mod gui;
 pub mod red_team;
 pub mod simulation;
 pub mod ats_simulation;
+pub mod api;
 
 /// A specialized result type for Analysis operations.
 pub type Result<T> = std::result::Result<T, AnalysisError>;
@@ -37,6 +39,12 @@ pub enum AnalysisError {
     /// A PDF processing error occurred.
     #[error("PDF error: {0}")]
     PdfError(String),
+    /// A PDF could not be opened.
+    #[error("cannot open PDF: {0}")]
+    PdfLoad(#[from] PdfLoadError),
+    /// A DOCX processing error occurred.
+    #[error("DOCX error: {0}")]
+    DocxError(String),
     /// An LLM API error occurred.
     #[error("LLM error: {0}")]
     LlmError(String),
@@ -44,3 +52,44 @@ pub enum AnalysisError {
     #[error("JSON error: {0}")]
     JsonError(String),
 }
+
+/// Why a PDF could not be opened.
+#[derive(Debug, thiserror::Error)]
+pub enum PdfLoadError {
+    /// The file does not exist.
+    #[error("file not found")]
+    NotFound,
+    /// The input has no `%PDF-` header.
+    #[error("not a PDF file")]
+    NotPdf,
+    /// The PDF is password protected and no password was given.
+    #[error("the PDF is encrypted; a password is required")]
+    Encrypted,
+    /// The given password does not open the PDF.
+    #[error("the PDF password is incorrect")]
+    WrongPassword,
+    /// The cross-reference table is missing or damaged.
+    #[error("corrupt cross-reference table ({0})")]
+    CorruptXref(String),
+    /// Any other read or parse failure.
+    #[error("{0}")]
+    Malformed(String),
+}
+
+impl From<lopdf::Error> for PdfLoadError {
+    fn from(error: lopdf::Error) -> Self {
+        match error {
+            lopdf::Error::IO(e) if e.kind() == std::io::ErrorKind::NotFound => PdfLoadError::NotFound,
+            // lopdf does not export its parse error type, so the cases are told apart by message.
+            lopdf::Error::Parse(e) if e.to_string() == "invalid file header" => PdfLoadError::NotPdf,
+            lopdf::Error::Parse(e) if e.to_string() == "invalid cross reference table" => {
+                PdfLoadError::CorruptXref(e.to_string())
+            }
+            e @ (lopdf::Error::Xref(_) | lopdf::Error::MissingXrefEntry | lopdf::Error::InvalidOffset(_)) => {
+                PdfLoadError::CorruptXref(e.to_string())
+            }
+            lopdf::Error::Decryption(_) => PdfLoadError::Encrypted,
+            e => PdfLoadError::Malformed(e.to_string()),
+        }
+    }
+}
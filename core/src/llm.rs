@@ -1,12 +1,43 @@
-use crate::config::LlmConfig;
+use crate::config::{ApiKeySource, LlmApi, LlmConfig, NetworkConfig};
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 use std::error::Error;
+use std::fs;
+use std::io::{self, BufRead, BufReader};
+use std::path::{Path, PathBuf};
+
+/// Per-user directory for cached responses when `cache_enabled` is set and no
+/// `cache_dir` is configured: `superpoweredcv/llm` under `%LOCALAPPDATA%` on
+/// Windows, `~/Library/Caches` on macOS and `$XDG_CACHE_HOME` (or `~/.cache`)
+/// elsewhere, falling back to the temp directory.
+pub fn default_cache_dir() -> PathBuf {
+    let env_dir = |name: &str| std::env::var_os(name).filter(|value| !value.is_empty()).map(PathBuf::from);
+    let base = if cfg!(windows) {
+        env_dir("LOCALAPPDATA")
+    } else if cfg!(target_os = "macos") {
+        env_dir("HOME").map(|home| home.join("Library").join("Caches"))
+    } else {
+        env_dir("XDG_CACHE_HOME").or_else(|| env_dir("HOME").map(|home| home.join(".cache")))
+    };
+    base.unwrap_or_else(std::env::temp_dir).join("superpoweredcv").join("llm")
+}
 
 #[derive(Serialize)]
 struct ChatCompletionRequest {
     model: String,
     messages: Vec<Message>,
     temperature: f32,
+    max_tokens: u32,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    stream: bool,
+    /// Asks for a final chunk carrying `usage` when streaming.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    stream_options: Option<StreamOptions>,
+}
+
+#[derive(Serialize)]
+struct StreamOptions {
+    include_usage: bool,
 }
 
 #[derive(Serialize, Deserialize, Clone)]
@@ -18,6 +49,14 @@ pub struct Message {
 #[derive(Deserialize)]
 struct ChatCompletionResponse {
     choices: Vec<Choice>,
+    #[serde(default)]
+    usage: Option<OpenAiUsage>,
+}
+
+#[derive(Deserialize)]
+struct OpenAiUsage {
+    prompt_tokens: u32,
+    completion_tokens: u32,
 }
 
 #[derive(Deserialize)]
@@ -25,50 +64,592 @@ struct Choice {
     message: Message,
 }
 
+/// Anthropic's version header value for the `/messages` schema used here.
+const ANTHROPIC_VERSION: &str = "2023-06-01";
+
+#[derive(Serialize)]
+struct AnthropicRequest {
+    model: String,
+    max_tokens: u32,
+    temperature: f32,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    system: String,
+    messages: Vec<Message>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    stream: bool,
+}
+
+#[derive(Deserialize)]
+struct AnthropicResponse {
+    content: Vec<AnthropicContent>,
+    #[serde(default)]
+    usage: Option<AnthropicUsage>,
+}
+
+#[derive(Deserialize)]
+struct AnthropicUsage {
+    input_tokens: u32,
+    output_tokens: u32,
+}
+
+#[derive(Deserialize)]
+struct AnthropicContent {
+    #[serde(default)]
+    text: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct GeminiRequest {
+    contents: Vec<GeminiContent>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    system_instruction: Option<GeminiContent>,
+    generation_config: GeminiGenerationConfig,
+}
+
+#[derive(Serialize, Deserialize)]
+struct GeminiContent {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    role: Option<String>,
+    #[serde(default)]
+    parts: Vec<GeminiPart>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct GeminiPart {
+    #[serde(default)]
+    text: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct GeminiGenerationConfig {
+    temperature: f32,
+    max_output_tokens: u32,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GeminiResponse {
+    #[serde(default)]
+    candidates: Vec<GeminiCandidate>,
+    #[serde(default)]
+    usage_metadata: Option<GeminiUsage>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GeminiUsage {
+    #[serde(default)]
+    prompt_token_count: u32,
+    #[serde(default)]
+    candidates_token_count: u32,
+}
+
+#[derive(Deserialize)]
+struct GeminiCandidate {
+    content: GeminiContent,
+}
+
+/// A completion along with the tokens it consumed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LlmResponse {
+    pub text: String,
+    pub prompt_tokens: u32,
+    pub completion_tokens: u32,
+    /// The counts are whitespace-split estimates because the provider
+    /// reported no usage or the response came from the cache.
+    pub estimated: bool,
+    /// Answered from the response cache without calling the provider.
+    pub cached: bool,
+}
+
+/// Cumulative token usage over a session, e.g. for display in the GUI.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct LlmUsage {
+    pub requests: u32,
+    pub prompt_tokens: u64,
+    pub completion_tokens: u64,
+    /// At least one recorded response carried estimated counts.
+    pub estimated: bool,
+}
+
+impl LlmUsage {
+    /// Adds a response's tokens. Cache hits cost nothing and are skipped.
+    pub fn record(&mut self, response: &LlmResponse) {
+        if response.cached {
+            return;
+        }
+        self.requests += 1;
+        self.prompt_tokens += u64::from(response.prompt_tokens);
+        self.completion_tokens += u64::from(response.completion_tokens);
+        self.estimated |= response.estimated;
+    }
+
+    pub fn total_tokens(&self) -> u64 {
+        self.prompt_tokens + self.completion_tokens
+    }
+}
+
+impl std::fmt::Display for LlmUsage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} request(s), {} prompt + {} completion tokens",
+            self.requests, self.prompt_tokens, self.completion_tokens
+        )?;
+        if self.estimated {
+            write!(f, " (partly estimated)")?;
+        }
+        Ok(())
+    }
+}
+
+/// Prompt and completion token counts as reported by the provider.
+type Usage = (u32, u32);
+
 pub struct LlmClient {
     config: LlmConfig,
     client: reqwest::blocking::Client,
+    key_source: ApiKeySource,
+    cache_dir: Option<PathBuf>,
 }
 
 impl LlmClient {
+    /// Creates a client, taking the API key from the environment, the OS
+    /// keyring or the config as described in [`LlmConfig::resolve_api_key`].
     pub fn new(config: LlmConfig) -> Self {
+        Self::with_network(config, &NetworkConfig::default())
+    }
+
+    /// Like [`new`](Self::new), sending requests through the configured proxy.
+    /// An invalid proxy URL falls back to the environment's proxy settings.
+    pub fn with_network(mut config: LlmConfig, network: &NetworkConfig) -> Self {
+        let (api_key, key_source) = config.resolve_api_key();
+        config.api_key = api_key;
+        let client = network
+            .client_builder()
+            .and_then(|builder| builder.build())
+            .unwrap_or_else(|_| reqwest::blocking::Client::new());
+        let cache_dir = config.cache_enabled.then(|| config.response_cache_dir());
         Self {
             config,
-            client: reqwest::blocking::Client::new(),
+            client,
+            key_source,
+            cache_dir,
         }
     }
 
+    /// Caches responses in `dir` regardless of `cache_enabled`.
+    pub fn with_cache_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.cache_dir = Some(dir.into());
+        self
+    }
+
+    /// Where the API key in use came from.
+    pub fn key_source(&self) -> &ApiKeySource {
+        &self.key_source
+    }
+
+    /// Sends `prompt` to the provider and returns the completion text. With a
+    /// cache directory set, an identical earlier request is answered from
+    /// disk instead.
     pub fn generate(&self, prompt: &str) -> Result<String, Box<dyn Error>> {
+        self.generate_with_usage(prompt).map(|response| response.text)
+    }
+
+    /// Like [`generate`](Self::generate), also returning the token usage the
+    /// provider reported.
+    pub fn generate_with_usage(&self, prompt: &str) -> Result<LlmResponse, Box<dyn Error>> {
+        self.cached(prompt, || self.generate_uncached(prompt))
+    }
+
+    /// Like [`generate_with_usage`](Self::generate_with_usage), but requests
+    /// a server-sent event stream and calls `on_token` with each chunk as it
+    /// arrives. Cache hits, Gemini's native API and servers that answer with
+    /// a plain JSON body are passed to `on_token` as a single chunk.
+    pub fn generate_streaming(&self, prompt: &str, mut on_token: impl FnMut(&str)) -> Result<LlmResponse, Box<dyn Error>> {
+        let response = self.cached(prompt, || self.stream_uncached(prompt, &mut on_token))?;
+        if response.cached {
+            on_token(&response.text);
+        }
+        Ok(response)
+    }
+
+    /// Answers from the cache directory when set and populated, otherwise
+    /// runs `fetch` and stores its result.
+    fn cached(
+        &self,
+        prompt: &str,
+        fetch: impl FnOnce() -> Result<(String, Option<Usage>), Box<dyn Error>>,
+    ) -> Result<LlmResponse, Box<dyn Error>> {
+        let Some(dir) = &self.cache_dir else {
+            let (text, usage) = fetch()?;
+            return Ok(self.response(prompt, text, usage, false));
+        };
+        let path = dir.join(format!("{}.txt", self.cache_key(prompt)));
+        if let Ok(cached) = fs::read_to_string(&path) {
+            return Ok(self.response(prompt, cached, None, true));
+        }
+        let (text, usage) = fetch()?;
+        // A failed cache write only costs a repeat call later.
+        if fs::create_dir_all(dir).is_ok() {
+            let _ = fs::write(&path, &text);
+        }
+        Ok(self.response(prompt, text, usage, false))
+    }
+
+    /// Wraps `text` with the reported usage, or a whitespace token estimate
+    /// of the system prompt, prompt and completion when there is none.
+    fn response(&self, prompt: &str, text: String, usage: Option<Usage>, cached: bool) -> LlmResponse {
+        let (prompt_tokens, completion_tokens) = usage.unwrap_or_else(|| {
+            let prompt_tokens = self.config.system_prompt.split_whitespace().count() + prompt.split_whitespace().count();
+            (prompt_tokens as u32, text.split_whitespace().count() as u32)
+        });
+        LlmResponse {
+            text,
+            prompt_tokens,
+            completion_tokens,
+            estimated: usage.is_none(),
+            cached,
+        }
+    }
+
+    /// Hash of everything that shapes the response: endpoint, model,
+    /// sampling settings, system prompt and the prompt itself.
+    fn cache_key(&self, prompt: &str) -> String {
+        let mut hasher = Sha256::new();
+        for part in [
+            self.config.api_base_url.as_str(),
+            self.config.model.as_str(),
+            &self.config.temperature.to_string(),
+            &self.config.max_tokens.to_string(),
+            self.config.system_prompt.as_str(),
+            prompt,
+        ] {
+            hasher.update(part.as_bytes());
+            hasher.update([0]);
+        }
+        format!("{:x}", hasher.finalize())
+    }
+
+    fn generate_uncached(&self, prompt: &str) -> Result<(String, Option<Usage>), Box<dyn Error>> {
+        match self.config.resolved_api() {
+            LlmApi::Anthropic => self.generate_anthropic(prompt),
+            LlmApi::Gemini => self.generate_gemini(prompt),
+            _ => self.generate_openai(prompt),
+        }
+    }
+
+    fn stream_uncached(&self, prompt: &str, on_token: &mut dyn FnMut(&str)) -> Result<(String, Option<Usage>), Box<dyn Error>> {
+        let api = self.config.resolved_api();
+        let builder = match api {
+            LlmApi::Anthropic => self.anthropic_request(prompt, true),
+            LlmApi::Gemini => {
+                let reply = self.generate_gemini(prompt)?;
+                on_token(&reply.0);
+                return Ok(reply);
+            }
+            _ => self.openai_request(prompt, true),
+        };
+
+        let response = builder.send()?;
+
+        if !response.status().is_success() {
+            return Err(format!("API request failed: {}", response.status()).into());
+        }
+
+        let is_event_stream = response
+            .headers()
+            .get(reqwest::header::CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .is_some_and(|value| value.starts_with("text/event-stream"));
+        if !is_event_stream {
+            // The server ignored `stream` and sent the whole completion.
+            let reply = match api {
+                LlmApi::Anthropic => anthropic_reply(response.json()?)?,
+                _ => openai_reply(response.json()?)?,
+            };
+            on_token(&reply.0);
+            return Ok(reply);
+        }
+
+        let mut text = String::new();
+        let (mut prompt_tokens, mut completion_tokens) = (None, None);
+        for line in BufReader::new(response).lines() {
+            let line = line?;
+            let Some(data) = line.strip_prefix("data:").map(str::trim) else {
+                continue;
+            };
+            if data == "[DONE]" {
+                break;
+            }
+            let Ok(chunk) = serde_json::from_str::<serde_json::Value>(data) else {
+                continue;
+            };
+            if let Some(error) = chunk.get("error") {
+                return Err(format!("Stream error: {}", error).into());
+            }
+            let token = match api {
+                LlmApi::Anthropic => chunk["delta"]["text"].as_str(),
+                _ => chunk["choices"][0]["delta"]["content"].as_str(),
+            };
+            if let Some(token) = token.filter(|token| !token.is_empty()) {
+                on_token(token);
+                text.push_str(token);
+            }
+            // OpenAI sends `usage` on the last chunk; Anthropic splits it
+            // between `message_start` and `message_delta`.
+            for usage in [&chunk["usage"], &chunk["message"]["usage"]] {
+                let count = |keys: [&str; 2]| keys.iter().find_map(|key| usage[*key].as_u64()).map(|n| n as u32);
+                prompt_tokens = count(["prompt_tokens", "input_tokens"]).or(prompt_tokens);
+                completion_tokens = count(["completion_tokens", "output_tokens"]).or(completion_tokens);
+            }
+        }
+        Ok((text, prompt_tokens.zip(completion_tokens)))
+    }
+
+    fn generate_openai(&self, prompt: &str) -> Result<(String, Option<Usage>), Box<dyn Error>> {
+        let response = self.openai_request(prompt, false).send()?;
+
+        if !response.status().is_success() {
+            return Err(format!("API request failed: {}", response.status()).into());
+        }
+
+        openai_reply(response.json()?)
+    }
+
+    fn openai_request(&self, prompt: &str, stream: bool) -> reqwest::blocking::RequestBuilder {
+        let mut messages = Vec::new();
+        if !self.config.system_prompt.is_empty() {
+            messages.push(Message {
+                role: "system".to_string(),
+                content: self.config.system_prompt.clone(),
+            });
+        }
+        messages.push(Message {
+            role: "user".to_string(),
+            content: prompt.to_string(),
+        });
+
         let request = ChatCompletionRequest {
             model: self.config.model.clone(),
+            messages,
+            temperature: self.config.temperature,
+            max_tokens: self.config.max_tokens,
+            stream,
+            stream_options: stream.then_some(StreamOptions { include_usage: true }),
+        };
+
+        let url = format!("{}/chat/completions", self.config.api_base_url.trim_end_matches('/'));
+        
+        let mut builder = self.client.post(&url)
+            .json(&request);
+
+        if let Some(key) = &self.config.api_key {
+            builder = builder.header("Authorization", format!("Bearer {}", key));
+        }
+
+        builder
+    }
+
+    fn generate_anthropic(&self, prompt: &str) -> Result<(String, Option<Usage>), Box<dyn Error>> {
+        let response = self.anthropic_request(prompt, false).send()?;
+
+        if !response.status().is_success() {
+            return Err(format!("API request failed: {}", response.status()).into());
+        }
+
+        anthropic_reply(response.json()?)
+    }
+
+    fn anthropic_request(&self, prompt: &str, stream: bool) -> reqwest::blocking::RequestBuilder {
+        // Anthropic takes the system prompt as a top-level field rather than a message.
+        let request = AnthropicRequest {
+            model: self.config.model.clone(),
+            max_tokens: self.config.max_tokens,
+            temperature: self.config.temperature,
+            system: self.config.system_prompt.clone(),
             messages: vec![Message {
                 role: "user".to_string(),
                 content: prompt.to_string(),
             }],
-            temperature: 0.7,
+            stream,
         };
 
-        let url = format!("{}/chat/completions", self.config.api_base_url.trim_end_matches('/'));
-        
+        let url = format!("{}/messages", self.config.api_base_url.trim_end_matches('/'));
+
         let mut builder = self.client.post(&url)
+            .header("anthropic-version", ANTHROPIC_VERSION)
             .json(&request);
 
         if let Some(key) = &self.config.api_key {
-            builder = builder.header("Authorization", format!("Bearer {}", key));
+            builder = builder.header("x-api-key", key);
+        }
+
+        builder
+    }
+
+    fn generate_gemini(&self, prompt: &str) -> Result<(String, Option<Usage>), Box<dyn Error>> {
+        let text_content = |role: Option<&str>, text: &str| GeminiContent {
+            role: role.map(str::to_string),
+            parts: vec![GeminiPart { text: text.to_string() }],
+        };
+        let request = GeminiRequest {
+            contents: vec![text_content(Some("user"), prompt)],
+            system_instruction: (!self.config.system_prompt.is_empty())
+                .then(|| text_content(None, &self.config.system_prompt)),
+            generation_config: GeminiGenerationConfig {
+                temperature: self.config.temperature,
+                max_output_tokens: self.config.max_tokens,
+            },
+        };
+
+        let url = format!(
+            "{}/models/{}:generateContent",
+            self.config.api_base_url.trim_end_matches('/'),
+            self.config.model
+        );
+
+        let mut builder = self.client.post(&url).json(&request);
+
+        if let Some(key) = &self.config.api_key {
+            builder = builder.header("x-goog-api-key", key);
         }
 
         let response = builder.send()?;
-        
+
         if !response.status().is_success() {
             return Err(format!("API request failed: {}", response.status()).into());
         }
 
-        let response_body: ChatCompletionResponse = response.json()?;
-        
-        if let Some(choice) = response_body.choices.first() {
-            Ok(choice.message.content.clone())
-        } else {
-            Err("No response from LLM".into())
+        let response_body: GeminiResponse = response.json()?;
+        let usage = response_body
+            .usage_metadata
+            .map(|usage| (usage.prompt_token_count, usage.candidates_token_count));
+
+        match response_body.candidates.first().and_then(|c| c.content.parts.first()) {
+            Some(part) => Ok((part.text.clone(), usage)),
+            None => Err("No response from LLM".into()),
+        }
+    }
+}
+
+fn openai_reply(body: ChatCompletionResponse) -> Result<(String, Option<Usage>), Box<dyn Error>> {
+    let usage = body.usage.map(|usage| (usage.prompt_tokens, usage.completion_tokens));
+    match body.choices.into_iter().next() {
+        Some(choice) => Ok((choice.message.content, usage)),
+        None => Err("No response from LLM".into()),
+    }
+}
+
+fn anthropic_reply(body: AnthropicResponse) -> Result<(String, Option<Usage>), Box<dyn Error>> {
+    let usage = body.usage.map(|usage| (usage.input_tokens, usage.output_tokens));
+    match body.content.into_iter().next() {
+        Some(block) => Ok((block.text, usage)),
+        None => Err("No response from LLM".into()),
+    }
+}
+
+/// Default Ollama server root; its OpenAI-compatible API lives under `/v1`.
+pub const OLLAMA_URL: &str = "http://localhost:11434";
+/// Default OpenAI-compatible local server used by LM Studio and LocalAI.
+pub const LM_STUDIO_URL: &str = "http://localhost:1234/v1";
+
+/// A model reported by a locally running inference server.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LocalModel {
+    pub name: String,
+    /// OpenAI-compatible base URL to store in `LlmConfig::api_base_url`.
+    pub api_base_url: String,
+}
+
+/// Models found by [`detect_local_models`] plus a message for each server that did not answer.
+#[derive(Debug, Default)]
+pub struct LocalModelScan {
+    pub models: Vec<LocalModel>,
+    pub errors: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct OllamaTags {
+    models: Vec<OllamaModel>,
+}
+
+#[derive(Deserialize)]
+struct OllamaModel {
+    name: String,
+}
+
+#[derive(Deserialize)]
+struct OpenAiModels {
+    data: Vec<OpenAiModel>,
+}
+
+#[derive(Deserialize)]
+struct OpenAiModel {
+    id: String,
+}
+
+/// Queries Ollama (`/api/tags`) and an OpenAI-compatible server (`/models`) for installed models.
+///
+/// Each request is bounded by `timeout`, so a server that is not running only costs a logged error.
+pub fn detect_local_models(ollama_url: &str, openai_url: &str, timeout: std::time::Duration) -> LocalModelScan {
+    let mut scan = LocalModelScan::default();
+    let client = match reqwest::blocking::Client::builder().timeout(timeout).build() {
+        Ok(client) => client,
+        Err(e) => {
+            scan.errors.push(format!("HTTP client error: {}", e));
+            return scan;
+        }
+    };
+
+    let ollama_url = ollama_url.trim_end_matches('/');
+    let ollama = client
+        .get(format!("{}/api/tags", ollama_url))
+        .send()
+        .and_then(|r| r.error_for_status())
+        .and_then(|r| r.json::<OllamaTags>());
+    match ollama {
+        Ok(tags) => scan.models.extend(tags.models.into_iter().map(|m| LocalModel {
+            name: m.name,
+            api_base_url: format!("{}/v1", ollama_url),
+        })),
+        Err(e) => scan.errors.push(format!("Ollama not reachable at {}: {}", ollama_url, e)),
+    }
+
+    let openai_url = openai_url.trim_end_matches('/');
+    let openai = client
+        .get(format!("{}/models", openai_url))
+        .send()
+        .and_then(|r| r.error_for_status())
+        .and_then(|r| r.json::<OpenAiModels>());
+    match openai {
+        Ok(list) => scan.models.extend(list.data.into_iter().map(|m| LocalModel {
+            name: m.id,
+            api_base_url: openai_url.to_string(),
+        })),
+        Err(e) => scan.errors.push(format!("LM Studio/LocalAI not reachable at {}: {}", openai_url, e)),
+    }
+
+    scan
+}
+
+/// Deletes every cached response in `dir`, returning how many were removed.
+/// A missing directory counts as an empty cache.
+pub fn clear_cache(dir: &Path) -> io::Result<usize> {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(0),
+        Err(e) => return Err(e),
+    };
+    let mut removed = 0;
+    for entry in entries {
+        let path = entry?.path();
+        if path.is_file() {
+            fs::remove_file(&path)?;
+            removed += 1;
         }
     }
+    Ok(removed)
 }
@@ -1,15 +1,18 @@
 use clap::{Parser, Subcommand};
 use config::{Config, File};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use superpoweredcv::api;
 use superpoweredcv::gui;
 use superpoweredcv::pipeline::{LoggingConfig, LogField, MetricSpec, MetricType, PipelineConfig, PipelineType};
-use superpoweredcv::analysis::{AnalysisPlan, AnalysisScenario, AnalysisEngine};
+use superpoweredcv::analysis::{scenario_schema, AnalysisPlan, AnalysisScenario, AnalysisEngine, CompositePlan, LocalPipelineExecutor, VariantSidecar};
 use superpoweredcv::attacks::{
     Intensity, InjectionPosition, JobAdPlacement, JobAdSource, PaddingStyle, ProfileConfig,
-    InjectionContent, LowVisibilityPalette, OffpageOffset, StructuralTarget
+    LowVisibilityPalette, ProfileConfigBuilder, StructuralTarget
 };
 use superpoweredcv::attacks::templates::default_templates;
 use superpoweredcv::generator::{self, ScrapedProfile};
+use superpoweredcv::profile::UserProfile;
+use superpoweredcv::config::AppConfig;
 use lopdf::dictionary;
 use std::fs::File as StdFile;
 
@@ -32,16 +35,32 @@ enum Commands {
         /// Path to the scenario definition file
         #[arg(short, long)]
         scenario: Option<PathBuf>,
+        /// Export the report to a `.json`, `.csv` or `.html` file
+        #[arg(long)]
+        report: Option<PathBuf>,
+        /// Name variant PDFs from a template, e.g. `{scenario}__{profile}__{template}__{hash8}`
+        #[arg(long)]
+        filename_template: Option<String>,
+        /// List the variants the scenario would produce without writing PDFs or calling pipelines
+        #[arg(long)]
+        dry_run: bool,
     },
     /// Run the built-in demo scenario
-    Demo,
+    Demo {
+        /// Export the report to a `.json`, `.csv` or `.html` file
+        #[arg(long)]
+        report: Option<PathBuf>,
+    },
     /// Validate a configuration file
     Validate,
     /// Generate a PDF from a scraped profile JSON
     Generate {
         /// Path to the profile JSON file
-        #[arg(short, long)]
-        profile: PathBuf,
+        #[arg(short, long, required_unless_present = "blank")]
+        profile: Option<PathBuf>,
+        /// Inject into a blank page instead of a PDF rendered from a profile
+        #[arg(long, conflicts_with = "profile")]
+        blank: bool,
         /// Output PDF path
         #[arg(short, long)]
         output: PathBuf,
@@ -70,17 +89,20 @@ enum Commands {
         #[arg(long)]
         job_description: Option<String>,
     },
-    /// Inject a payload into an existing PDF
+    /// Inject a payload into an existing PDF or DOCX
     Inject {
-        /// Path to the input PDF
+        /// Path to the input PDF or DOCX
         #[arg(short, long)]
         input: PathBuf,
-        /// Path to the output PDF
+        /// Path to the output file
         #[arg(short, long)]
         output: PathBuf,
         /// Type of injection
-        #[arg(long, value_enum)]
+        #[arg(long, value_enum, default_value_t = CliInjectionType::None)]
         type_: CliInjectionType,
+        /// Reproduce a variant from its `<variant_id>.profile.json` sidecar
+        #[arg(long)]
+        profiles_file: Option<PathBuf>,
         /// Payload content (text, url, or code)
         #[arg(long)]
         payload: Option<String>,
@@ -96,12 +118,94 @@ enum Commands {
         /// Job Description
         #[arg(long)]
         job_description: Option<String>,
+
+        /// Append the injection as an incremental update, keeping the input's bytes intact
+        #[arg(long)]
+        incremental: bool,
+
+        /// Deflate the injected content streams so their text is not readable in the raw bytes
+        #[arg(long)]
+        compress: bool,
+
+        /// Password for an encrypted input PDF
+        #[arg(long)]
+        password: Option<String>,
     },
     /// Preview the injection layout (generates a dummy PDF)
     Preview {
         /// Output path for the preview PDF
         #[arg(short, long)]
         output: PathBuf,
+
+        /// Injection types to mark on the page (repeatable)
+        #[arg(long, value_enum)]
+        injection: Vec<CliInjectionType>,
+
+        /// Position (for VisibleMeta)
+        #[arg(long, value_enum, default_value_t = CliPosition::Header)]
+        position: CliPosition,
+    },
+    /// Generate one variant per (profile type, default template) pair
+    Batch {
+        /// Input PDF, or a profile JSON to render into a base PDF first
+        #[arg(short, long)]
+        input: PathBuf,
+        /// Directory receiving `<variant_id>.pdf` files
+        #[arg(short, long)]
+        output_dir: PathBuf,
+        /// Injection types to apply, comma separated or repeated
+        #[arg(long, value_enum, value_delimiter = ',', required = true)]
+        profile_type: Vec<CliInjectionType>,
+        /// Seed for randomized injection content, for reproducible hashes
+        #[arg(long)]
+        seed: Option<u64>,
+    },
+    /// Scan a PDF, or a directory of PDFs, for hidden injection techniques.
+    /// Exits with status 1 when anything is flagged and 2 when a file cannot be scanned.
+    Scan {
+        /// PDF file or directory to scan recursively
+        #[arg(short, long)]
+        input: PathBuf,
+        /// Print findings as JSON instead of a table
+        #[arg(long)]
+        json: bool,
+    },
+    /// Convert a resume between JSON Resume, UserProfile and scraped profile JSON
+    Convert {
+        /// Format of the input file
+        #[arg(long, value_enum)]
+        from: CliProfileFormat,
+        /// Format to write
+        #[arg(long, value_enum)]
+        to: CliProfileFormat,
+        /// Path to the input JSON file
+        #[arg(short, long)]
+        input: PathBuf,
+        /// Write the result to a file instead of stdout
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Score every PDF in a directory with the local keyword heuristic and
+    /// print them ranked. Exits with status 2 when a file cannot be scored.
+    Score {
+        /// Directory of already-mutated PDFs
+        #[arg(short, long)]
+        dir: PathBuf,
+    },
+    /// Convert a scenario file between JSON, YAML and TOML, validating it on the way
+    ConvertScenario {
+        /// Path to the input scenario (`.json`, `.yaml`, `.yml` or `.toml`)
+        #[arg(short, long)]
+        input: PathBuf,
+        /// Path to write; the format follows the extension
+        #[arg(short, long)]
+        output: PathBuf,
+    },
+    /// Print the JSON Schema for scenario files
+    Schema {
+        /// Write the schema to a file instead of stdout
+        #[arg(short, long)]
+        output: Option<PathBuf>,
     },
     /// Open the documentation
     Docs,
@@ -121,6 +225,16 @@ enum CliInjectionType {
     InlineJobAd,
 }
 
+#[derive(clap::ValueEnum, Clone, Debug)]
+enum CliProfileFormat {
+    /// JSON Resume (jsonresume.org)
+    Jsonresume,
+    /// The structured `UserProfile` model
+    Profile,
+    /// The flat `ScrapedProfile` consumed by `generate`
+    Scraped,
+}
+
 #[derive(clap::ValueEnum, Clone, Debug)]
 enum CliGenerationType {
     Static,
@@ -146,23 +260,68 @@ fn main() {
     let cli = Cli::parse();
 
     match &cli.command {
-        Some(Commands::Analyze { scenario }) => {
+        Some(Commands::Analyze { scenario, report, filename_template, dry_run }) => {
             if let Some(path) = scenario {
-                run_scenario_from_file(path);
+                run_scenario_from_file(path, report.as_deref(), filename_template.clone(), *dry_run);
             } else {
                 eprintln!("Error: --scenario argument is required for 'analyze' command.");
             }
         }
-        Some(Commands::Demo) => {
-            run_demo_scenario();
+        Some(Commands::Demo { report }) => {
+            run_demo_scenario(report.as_deref());
         }
-        Some(Commands::Inject { input, output, type_, payload, phrases, generation_type, job_description }) => {
-            println!("Injecting {:?} into {:?} -> {:?}", type_, input, output);
-            inject_pdf(input, output, type_, payload, phrases, generation_type, job_description);
+        Some(Commands::Inject { input, output, type_, profiles_file, payload, phrases, generation_type, job_description, incremental, compress, password }) => {
+            if let Some(sidecar_path) = profiles_file {
+                println!("Reproducing {:?} into {:?} -> {:?}", sidecar_path, input, output);
+                inject_from_sidecar(input, output, sidecar_path, *incremental, *compress, password);
+            } else {
+                println!("Injecting {:?} into {:?} -> {:?}", type_, input, output);
+                inject_pdf(input, output, type_, payload, phrases, generation_type, job_description, *incremental, *compress, password);
+            }
         }
-        Some(Commands::Preview { output }) => {
+        Some(Commands::Preview { output, injection, position }) => {
             println!("Generating preview at {:?}", output);
-            // Placeholder for preview generation
+            generate_preview(output, injection, position);
+        }
+        Some(Commands::Batch { input, output_dir, profile_type, seed }) => {
+            run_batch(input, output_dir, profile_type, *seed);
+        }
+        Some(Commands::Scan { input, json }) => {
+            let code = scan_pdfs(input, *json);
+            if code != 0 {
+                std::process::exit(code);
+            }
+        }
+        Some(Commands::Convert { from, to, input, output }) => {
+            if let Err(e) = convert_profile(from, to, input, output.as_deref()) {
+                eprintln!("Failed to convert profile: {}", e);
+                std::process::exit(1);
+            }
+        }
+        Some(Commands::Score { dir }) => {
+            let code = score_dir(dir);
+            if code != 0 {
+                std::process::exit(code);
+            }
+        }
+        Some(Commands::ConvertScenario { input, output }) => {
+            match AnalysisScenario::load(input).and_then(|scenario| scenario.write(output)) {
+                Ok(()) => println!("Converted scenario written to {}", output.display()),
+                Err(e) => {
+                    eprintln!("Failed to convert scenario: {}", e);
+                    std::process::exit(1);
+                }
+            }
+        }
+        Some(Commands::Schema { output }) => {
+            let schema = serde_json::to_string_pretty(&scenario_schema()).unwrap();
+            match output {
+                Some(path) => match std::fs::write(path, schema) {
+                    Ok(()) => println!("Schema written to {}", path.display()),
+                    Err(e) => eprintln!("Failed to write schema: {}", e),
+                },
+                None => println!("{}", schema),
+            }
         }
         Some(Commands::Docs) => {
             if open::that("https://github.com/supermarsx/superpoweredcv").is_err() {
@@ -171,12 +330,16 @@ fn main() {
         }
         Some(Commands::Validate) => {
             if let Some(config_path) = &cli.config {
-                validate_config(config_path);
+                let code = validate_config(config_path);
+                if code != 0 {
+                    std::process::exit(code);
+                }
             } else {
                 eprintln!("Error: --config argument is required for 'validate' command.");
             }
         }
-        Some(Commands::Generate { profile, output, injection, intensity, position, phrases, generation_type, job_description }) => {
+        Some(Commands::Generate { profile, blank, output, injection, intensity, position, phrases, generation_type, job_description }) => {
+            let profile = if *blank { None } else { profile.as_ref() };
             generate_pdf_from_json(profile, output, injection, intensity, position, phrases, generation_type, job_description);
         }
         None => {
@@ -188,10 +351,36 @@ fn main() {
     }
 }
 
-use superpoweredcv::pdf::{PdfMutator, RealPdfMutator, PdfMutationRequest};
+use superpoweredcv::pdf::{is_blank_base, RealPdfMutator, PdfMutationRequest, BLANK_BASE_PDF, scan_path};
 
+fn convert_profile(from: &CliProfileFormat, to: &CliProfileFormat, input: &Path, output: Option<&Path>) -> Result<(), String> {
+    let text = std::fs::read_to_string(input).map_err(|e| e.to_string())?;
+    let value: serde_json::Value = serde_json::from_str(&text).map_err(|e| e.to_string())?;
+    let profile = match from {
+        CliProfileFormat::Jsonresume => UserProfile::from_json_resume(value).map_err(|e| e.to_string())?,
+        CliProfileFormat::Profile => serde_json::from_value(value).map_err(|e| e.to_string())?,
+        CliProfileFormat::Scraped => UserProfile::from(&serde_json::from_value::<ScrapedProfile>(value).map_err(|e| e.to_string())?),
+    };
+    let converted = match to {
+        CliProfileFormat::Jsonresume => profile.to_json_resume(),
+        CliProfileFormat::Profile => serde_json::to_value(&profile).map_err(|e| e.to_string())?,
+        CliProfileFormat::Scraped => serde_json::to_value(ScrapedProfile::from(&profile)).map_err(|e| e.to_string())?,
+    };
+    let json = serde_json::to_string_pretty(&converted).map_err(|e| e.to_string())?;
+    match output {
+        Some(path) => {
+            std::fs::write(path, json).map_err(|e| e.to_string())?;
+            println!("Converted profile written to {}", path.display());
+        }
+        None => println!("{}", json),
+    }
+    Ok(())
+}
+
+/// Renders `profile_path` and injects into it, or injects into a blank page
+/// when no profile is given.
 fn generate_pdf_from_json(
-    profile_path: &PathBuf, 
+    profile_path: Option<&PathBuf>,
     output_path: &PathBuf,
     injection: &CliInjectionType,
     intensity: &CliIntensity,
@@ -200,210 +389,375 @@ fn generate_pdf_from_json(
     generation_type: &CliGenerationType,
     job_description: &Option<String>
 ) {
-    let file = match StdFile::open(profile_path) {
-        Ok(f) => f,
-        Err(e) => {
-            eprintln!("Failed to open profile file: {}", e);
-            return;
+    // 1. Generate Clean PDF (or let the mutator start from a blank page)
+    let temp_path = match profile_path {
+        Some(profile_path) => {
+            let file = match StdFile::open(profile_path) {
+                Ok(f) => f,
+                Err(e) => {
+                    eprintln!("Failed to open profile file: {}", e);
+                    return;
+                }
+            };
+
+            let profile: ScrapedProfile = match serde_json::from_reader(file) {
+                Ok(p) => p,
+                Err(e) => {
+                    eprintln!("Failed to parse profile JSON: {}", e);
+                    return;
+                }
+            };
+
+            let temp_path = std::env::temp_dir().join("superpoweredcv_cli_temp.pdf");
+            if let Err(e) = generator::generate_pdf(&profile, &temp_path, None) {
+                eprintln!("Failed to generate base PDF: {}", e);
+                return;
+            }
+            temp_path
         }
+        None => PathBuf::from(BLANK_BASE_PDF),
     };
-    
-    let profile: ScrapedProfile = match serde_json::from_reader(file) {
-        Ok(p) => p,
+
+    // 2. Prepare Injection
+    let injection_config = cli_profile(injection, intensity, position, phrases, generation_type, job_description);
+
+    if let Some(config) = injection_config {
+        match api::inject_pdf(&temp_path, output_path, vec![config], api::default_template()) {
+            Ok(_) => println!("PDF generated and injected successfully at {}", output_path.display()),
+            Err(e) => eprintln!("Failed to inject PDF: {}", e),
+        }
+    } else if is_blank_base(&temp_path) {
+        match superpoweredcv::pdf_utils::create_blank_pdf().save(output_path) {
+            Ok(_) => println!("Blank PDF generated successfully at {}", output_path.display()),
+            Err(e) => eprintln!("Failed to write blank PDF: {}", e),
+        }
+    } else {
+        // Just move the temp file if no injection
+        if let Err(e) = std::fs::rename(&temp_path, output_path) {
+            eprintln!("Failed to move output file: {}", e);
+        } else {
+            println!("Clean PDF generated successfully at {}", output_path.display());
+        }
+    }
+}
+
+/// Builds the profile selected by the `generate`/`preview` injection flags.
+fn cli_profile(
+    injection: &CliInjectionType,
+    intensity: &CliIntensity,
+    position: &CliPosition,
+    phrases: &[String],
+    generation_type: &CliGenerationType,
+    job_description: &Option<String>,
+) -> Option<ProfileConfig> {
+    let generation_type = match generation_type {
+        CliGenerationType::Static => superpoweredcv::attacks::templates::GenerationType::Static,
+        CliGenerationType::AdTargeted => superpoweredcv::attacks::templates::GenerationType::AdTargeted,
+        CliGenerationType::LlmControl => superpoweredcv::attacks::templates::GenerationType::LlmControl,
+        CliGenerationType::Pollution => superpoweredcv::attacks::templates::GenerationType::Pollution,
+    };
+    let intensity = match intensity {
+        CliIntensity::Soft => Intensity::Soft,
+        CliIntensity::Medium => Intensity::Medium,
+        CliIntensity::Aggressive => Intensity::Aggressive,
+    };
+
+    let builder = match injection {
+        CliInjectionType::None => return None,
+        CliInjectionType::VisibleMeta => ProfileConfigBuilder::visible_meta()
+            .with_position(match position {
+                CliPosition::Header => InjectionPosition::Header,
+                CliPosition::Footer => InjectionPosition::Footer,
+            })
+            .with_intensity(intensity),
+        CliInjectionType::LowVis => ProfileConfigBuilder::low_visibility(),
+        CliInjectionType::Offpage => ProfileConfigBuilder::offpage(),
+        CliInjectionType::TrackingPixel => {
+            return Some(ProfileConfig::TrackingPixel {
+                url: phrases.first().cloned().unwrap_or_else(|| "https://canarytokens.org/pixel".to_string()),
+                cover_whole_page: true,
+            });
+        }
+        CliInjectionType::CodeInjection => {
+            return Some(ProfileConfig::CodeInjection {
+                payload: phrases.join(" "),
+            });
+        }
+        CliInjectionType::UnderlayText => return Some(ProfileConfig::UnderlayText),
+        CliInjectionType::StructuralFields => {
+            return Some(ProfileConfig::StructuralFields {
+                targets: vec![StructuralTarget::PdfTag],
+            });
+        }
+        CliInjectionType::PaddingNoise => ProfileConfigBuilder::padding_noise().with_intensity(intensity),
+        CliInjectionType::InlineJobAd => ProfileConfigBuilder::inline_job_ad(),
+    };
+
+    let mut builder = builder
+        .with_phrases(phrases.iter().cloned())
+        .with_generation_type(generation_type);
+    if let Some(job_description) = job_description {
+        builder = builder.with_job_description(job_description.clone());
+    }
+    match builder.build() {
+        Ok(profile) => Some(profile),
         Err(e) => {
-            eprintln!("Failed to parse profile JSON: {}", e);
+            eprintln!("Invalid injection: {}", e);
+            None
+        }
+    }
+}
+
+fn generate_preview(output_path: &Path, injections: &[CliInjectionType], position: &CliPosition) {
+    let profiles: Vec<ProfileConfig> = injections
+        .iter()
+        .filter_map(|injection| cli_profile(injection, &CliIntensity::Medium, position, &[], &CliGenerationType::Static, &None))
+        .collect();
+    if profiles.is_empty() {
+        println!("No injection types given; the preview shows only the page layout.");
+    }
+
+    match generator::generate_layout_preview(&profiles, output_path) {
+        Ok(()) => println!("Preview written to {}", output_path.display()),
+        Err(e) => eprintln!("Failed to generate preview: {}", e),
+    }
+}
+
+fn run_batch(input_path: &Path, output_dir: &Path, profile_types: &[CliInjectionType], seed: Option<u64>) {
+    let is_json = input_path
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("json"));
+    let base_pdf = if is_json {
+        let profile: ScrapedProfile = match StdFile::open(input_path).map_err(|e| e.to_string())
+            .and_then(|f| serde_json::from_reader(f).map_err(|e| e.to_string()))
+        {
+            Ok(p) => p,
+            Err(e) => {
+                eprintln!("Failed to read profile JSON: {}", e);
+                return;
+            }
+        };
+        if let Err(e) = std::fs::create_dir_all(output_dir) {
+            eprintln!("Failed to create output directory: {}", e);
+            return;
+        }
+        let base = output_dir.join("base.pdf");
+        if let Err(e) = generator::generate_pdf(&profile, &base, None) {
+            eprintln!("Failed to generate base PDF: {}", e);
             return;
         }
+        base
+    } else {
+        input_path.to_path_buf()
     };
 
-    // 1. Generate Clean PDF
-    let temp_path = std::env::temp_dir().join("superpoweredcv_cli_temp.pdf");
-    if let Err(e) = generator::generate_pdf(&profile, &temp_path, None) {
-        eprintln!("Failed to generate base PDF: {}", e);
+    let profiles: Vec<ProfileConfig> = profile_types
+        .iter()
+        .filter_map(|injection| cli_profile(injection, &CliIntensity::Medium, &CliPosition::Header, &[], &CliGenerationType::Static, &None))
+        .collect();
+    if profiles.is_empty() {
+        eprintln!("No injecting profile types given.");
         return;
     }
 
-    // 2. Prepare Injection
-    let content = InjectionContent {
-        phrases: phrases.clone(),
-        generation_type: match generation_type {
-            CliGenerationType::Static => superpoweredcv::attacks::templates::GenerationType::Static,
-            CliGenerationType::AdTargeted => superpoweredcv::attacks::templates::GenerationType::AdTargeted,
-            CliGenerationType::LlmControl => superpoweredcv::attacks::templates::GenerationType::LlmControl,
-            CliGenerationType::Pollution => superpoweredcv::attacks::templates::GenerationType::Pollution,
-        },
-        job_description: job_description.clone(),
+    let engine = AnalysisEngine::new(default_templates());
+    let mutator = RealPdfMutator::new(output_dir);
+    let variants = match engine.generate_variants(&base_pdf, &profiles, &mutator, seed) {
+        Ok(v) => v,
+        Err(e) => {
+            eprintln!("Batch failed: {}", e);
+            return;
+        }
     };
 
-    let injection_config = match injection {
-        CliInjectionType::None => None,
-        CliInjectionType::VisibleMeta => Some(ProfileConfig::VisibleMetaBlock {
-            position: match position {
-                CliPosition::Header => InjectionPosition::Header,
-                CliPosition::Footer => InjectionPosition::Footer,
-            },
-            intensity: match intensity {
-                CliIntensity::Soft => Intensity::Soft,
-                CliIntensity::Medium => Intensity::Medium,
-                CliIntensity::Aggressive => Intensity::Aggressive,
-            },
-            content,
-        }),
-        CliInjectionType::LowVis => Some(ProfileConfig::LowVisibilityBlock {
-            font_size_min: 1,
-            font_size_max: 1,
-            color_profile: LowVisibilityPalette::Gray,
-            content,
-        }),
-        CliInjectionType::Offpage => Some(ProfileConfig::OffpageLayer {
-            offset_strategy: OffpageOffset::BottomClip,
-            content,
-        }),
-        CliInjectionType::TrackingPixel => Some(ProfileConfig::TrackingPixel {
-            url: phrases.first().cloned().unwrap_or_else(|| "https://canarytokens.org/pixel".to_string()),
-        }),
-        CliInjectionType::CodeInjection => Some(ProfileConfig::CodeInjection {
-            payload: phrases.join(" "),
-        }),
-        CliInjectionType::UnderlayText => Some(ProfileConfig::UnderlayText),
-        CliInjectionType::StructuralFields => Some(ProfileConfig::StructuralFields {
-            targets: vec![StructuralTarget::PdfTag],
-        }),
-        CliInjectionType::PaddingNoise => Some(ProfileConfig::PaddingNoise {
-            padding_tokens_before: 100,
-            padding_tokens_after: 100,
-            padding_style: PaddingStyle::JobRelated,
-            content,
-        }),
-        CliInjectionType::InlineJobAd => Some(ProfileConfig::InlineJobAd {
-            job_ad_source: JobAdSource::Inline,
-            placement: JobAdPlacement::Back,
-            ad_excerpt_ratio: 1.0,
-            content,
-        }),
-    };
+    let width = variants.iter().map(|v| v.variant_id.len() + 4).max().unwrap_or(0).max("FILE".len());
+    println!("{:<width$}  HASH", "FILE");
+    for variant in &variants {
+        let file = variant
+            .mutated_pdf
+            .as_ref()
+            .and_then(|p| p.file_name())
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default();
+        println!("{:<width$}  {}", file, variant.variant_hash.as_deref().unwrap_or("-"));
+    }
+    println!("Generated {} variant(s) in {}", variants.len(), output_dir.display());
+}
 
-    if let Some(config) = injection_config {
-        let mutator = RealPdfMutator::new(output_path.parent().unwrap());
-        let request = PdfMutationRequest {
-            base_pdf: temp_path,
-            profiles: vec![config],
-            template: default_templates().into_iter().find(|t| t.id == "default").unwrap_or_else(|| default_templates()[0].clone()),
-            variant_id: Some(output_path.file_stem().unwrap().to_string_lossy().to_string()),
-        };
+/// Prints scan results and returns the process exit code.
+fn scan_pdfs(input: &Path, json: bool) -> i32 {
+    let scans = match scan_path(input) {
+        Ok(s) => s,
+        Err(e) => {
+            eprintln!("Failed to scan {}: {}", input.display(), e);
+            return 2;
+        }
+    };
 
-        match mutator.mutate(request) {
-            Ok(res) => {
-                // Rename result to final output
-                if let Err(e) = std::fs::rename(&res.mutated_pdf, output_path) {
-                    eprintln!("Failed to move output file: {}", e);
-                } else {
-                    println!("PDF generated and injected successfully at {}", output_path.display());
+    if json {
+        match serde_json::to_string_pretty(&scans) {
+            Ok(out) => println!("{}", out),
+            Err(e) => eprintln!("Failed to serialize findings: {}", e),
+        }
+    } else {
+        for scan in &scans {
+            println!("== {} ==", scan.path.display());
+            if let Some(error) = &scan.error {
+                println!("  ERROR: {}", error);
+                continue;
+            }
+            if scan.findings.is_empty() {
+                println!("  No findings.");
+                continue;
+            }
+            println!("  {:<20} {:<5} {:<5} DESCRIPTION", "CATEGORY", "PAGE", "CONF");
+            for finding in &scan.findings {
+                let page = finding.page.map(|p| p.to_string()).unwrap_or_else(|| "-".into());
+                println!("  {:<20} {:<5} {:<5.2} {}", finding.category.label(), page, finding.confidence, finding.description);
+            }
+            println!("  Risk score: {:.2}", scan.risk_score);
+        }
+        if scans.len() > 1 {
+            println!();
+            println!("Summary:");
+            for scan in &scans {
+                match &scan.error {
+                    Some(_) => println!("  {}: error", scan.path.display()),
+                    None => println!("  {}: {} finding(s), risk {:.2}", scan.path.display(), scan.findings.len(), scan.risk_score),
                 }
             }
-            Err(e) => eprintln!("Failed to inject PDF: {}", e),
         }
+    }
+
+    if scans.iter().any(|s| !s.findings.is_empty()) {
+        1
+    } else if scans.iter().any(|s| s.error.is_some()) {
+        2
     } else {
-        // Just move the temp file if no injection
-        if let Err(e) = std::fs::rename(&temp_path, output_path) {
-            eprintln!("Failed to move output file: {}", e);
-        } else {
-            println!("Clean PDF generated successfully at {}", output_path.display());
+        0
+    }
+}
+
+fn score_dir(dir: &Path) -> i32 {
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) => {
+            eprintln!("Failed to read {}: {}", dir.display(), e);
+            return 2;
+        }
+    };
+    let mut files: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.is_file() && path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("pdf")))
+        .collect();
+    files.sort();
+
+    let executor = LocalPipelineExecutor::new();
+    let mut failed = false;
+    let mut impacts = Vec::new();
+    for path in &files {
+        match executor.score_file(path) {
+            Ok(impact) => impacts.push(impact),
+            Err(e) => {
+                eprintln!("Failed to score {}: {}", path.display(), e);
+                failed = true;
+            }
+        }
+    }
+    impacts.sort_by(|a, b| b.score_after.unwrap_or(0.0).total_cmp(&a.score_after.unwrap_or(0.0)));
+
+    if impacts.is_empty() {
+        println!("No PDFs scored in {}", dir.display());
+    } else {
+        println!("{:<5} {:>6} {:<14} {:<10} FILE", "RANK", "SCORE", "CLASS", "INJECTION");
+        for (rank, impact) in impacts.iter().enumerate() {
+            let injection = match impact.phrase_positions.first() {
+                Some(position) => format!("at {:.2}", position.offset),
+                None => "-".to_string(),
+            };
+            println!(
+                "{:<5} {:>6.1} {:<14} {:<10} {}",
+                rank + 1,
+                impact.score_after.unwrap_or(0.0),
+                impact.classification_after.as_deref().unwrap_or("-"),
+                injection,
+                impact.mutated_pdf.as_deref().map(|path| path.display().to_string()).unwrap_or_default(),
+            );
         }
     }
+
+    if failed { 2 } else { 0 }
 }
 
+#[allow(clippy::too_many_arguments)]
 fn inject_pdf(
-    input_path: &PathBuf, 
-    output_path: &PathBuf, 
+    input_path: &Path,
+    output_path: &Path,
     injection_type: &CliInjectionType, 
     payload: &Option<String>,
     phrases: &Vec<String>,
     generation_type: &CliGenerationType,
-    job_description: &Option<String>
+    job_description: &Option<String>,
+    incremental: bool,
+    compress: bool,
+    password: &Option<String>,
 ) {
     let mut effective_phrases = phrases.clone();
     if let Some(p) = payload {
         effective_phrases.push(p.clone());
     }
 
-    let content = InjectionContent {
-        phrases: effective_phrases.clone(),
-        generation_type: match generation_type {
-            CliGenerationType::Static => superpoweredcv::attacks::templates::GenerationType::Static,
-            CliGenerationType::AdTargeted => superpoweredcv::attacks::templates::GenerationType::AdTargeted,
-            CliGenerationType::LlmControl => superpoweredcv::attacks::templates::GenerationType::LlmControl,
-            CliGenerationType::Pollution => superpoweredcv::attacks::templates::GenerationType::Pollution,
-        },
-        job_description: job_description.clone(),
+    if matches!(injection_type, CliInjectionType::None) {
+        eprintln!("No injection type specified.");
+        return;
+    }
+    // `cli_profile` reports build errors itself.
+    let Some(config) = cli_profile(injection_type, &CliIntensity::Medium, &CliPosition::Footer, &effective_phrases, generation_type, job_description) else {
+        return;
     };
 
-    let injection_config = match injection_type {
-        CliInjectionType::None => None,
-        CliInjectionType::VisibleMeta => Some(ProfileConfig::VisibleMetaBlock {
-            position: InjectionPosition::Footer,
-            intensity: Intensity::Medium,
-            content,
-        }),
-        CliInjectionType::LowVis => Some(ProfileConfig::LowVisibilityBlock {
-            font_size_min: 1,
-            font_size_max: 1,
-            color_profile: LowVisibilityPalette::Gray,
-            content,
-        }),
-        CliInjectionType::Offpage => Some(ProfileConfig::OffpageLayer {
-            offset_strategy: OffpageOffset::BottomClip,
-            content,
-        }),
-        CliInjectionType::TrackingPixel => Some(ProfileConfig::TrackingPixel {
-            url: effective_phrases.first().cloned().unwrap_or_else(|| "https://canarytokens.org/pixel".to_string()),
-        }),
-        CliInjectionType::CodeInjection => Some(ProfileConfig::CodeInjection {
-            payload: effective_phrases.join(" "),
-        }),
-        CliInjectionType::UnderlayText => Some(ProfileConfig::UnderlayText),
-        CliInjectionType::StructuralFields => Some(ProfileConfig::StructuralFields {
-            targets: vec![StructuralTarget::PdfTag],
-        }),
-        CliInjectionType::PaddingNoise => Some(ProfileConfig::PaddingNoise {
-            padding_tokens_before: 100,
-            padding_tokens_after: 100,
-            padding_style: PaddingStyle::JobRelated,
-            content,
-        }),
-        CliInjectionType::InlineJobAd => Some(ProfileConfig::InlineJobAd {
-            job_ad_source: JobAdSource::Inline,
-            placement: JobAdPlacement::Back,
-            ad_excerpt_ratio: 1.0,
-            content,
-        }),
+    let request = PdfMutationRequest {
+        base_pdf: input_path.to_path_buf(),
+        profiles: vec![config],
+        incremental,
+        password: password.clone(),
+        compress,
+        ..Default::default()
     };
 
-    if let Some(config) = injection_config {
-        let mutator = RealPdfMutator::new(output_path.parent().unwrap());
-        let request = PdfMutationRequest {
-            base_pdf: input_path.clone(),
-            profiles: vec![config],
-            template: default_templates().into_iter().find(|t| t.id == "default").unwrap_or_else(|| default_templates()[0].clone()),
-            variant_id: Some(output_path.file_stem().unwrap().to_string_lossy().to_string()),
-        };
+    match api::inject_request(request, output_path) {
+        Ok(_) => println!("Injected successfully at {}", output_path.display()),
+        Err(e) => eprintln!("Failed to inject PDF: {}", e),
+    }
+}
 
-        match mutator.mutate(request) {
-            Ok(res) => {
-                if let Err(e) = std::fs::rename(&res.mutated_pdf, output_path) {
-                    eprintln!("Failed to move output file: {}", e);
-                } else {
-                    println!("PDF injected successfully at {}", output_path.display());
-                }
+fn inject_from_sidecar(input_path: &Path, output_path: &Path, sidecar_path: &Path, incremental: bool, compress: bool, password: &Option<String>) {
+    let sidecar = match VariantSidecar::load(sidecar_path) {
+        Ok(s) => s,
+        Err(e) => {
+            eprintln!("Failed to read profiles file: {}", e);
+            return;
+        }
+    };
+
+    let mut request = sidecar.to_request(input_path.to_path_buf());
+    request.variant_id = None;
+    request.incremental = incremental;
+    request.compress = compress;
+    request.password = password.clone();
+
+    match api::inject_request(request, output_path) {
+        Ok(res) => {
+            println!("Injected successfully at {}", output_path.display());
+            if sidecar.variant_hash.is_some() && sidecar.variant_hash == res.variant_hash {
+                println!("Hash matches the original variant.");
             }
-            Err(e) => eprintln!("Failed to inject PDF: {}", e),
         }
-    } else {
-        eprintln!("No injection type specified.");
+        Err(e) => eprintln!("Failed to inject PDF: {}", e),
     }
 }
 
-fn run_scenario_from_file(path: &PathBuf) {
+fn run_scenario_from_file(path: &PathBuf, report_path: Option<&Path>, filename_template: Option<String>, dry_run: bool) {
     println!("Loading scenario from: {}", path.display());
     
     let settings = Config::builder()
@@ -414,10 +768,31 @@ fn run_scenario_from_file(path: &PathBuf) {
         Ok(config) => {
             match config.try_deserialize::<AnalysisScenario>() {
                 Ok(scenario) => {
-                    let engine = AnalysisEngine::new(default_templates());
+                    let mut engine = AnalysisEngine::new(default_templates()).with_app_config(AppConfig::load());
+                    if let Some(template) = filename_template {
+                        engine = engine.with_filename_template(template);
+                    }
                     println!("Starting Analysis Scenario: {}", scenario.scenario_id);
-                    match engine.run_scenario(&scenario) {
-                        Ok(report) => print_report(&report),
+                    let result = if dry_run {
+                        engine.run_dry(&scenario)
+                    } else {
+                        engine.run_scenario_with_progress(&scenario, |event| {
+                            println!(
+                                "[{}/{}] {:?} {}",
+                                event.plan_index + 1,
+                                event.total_plans,
+                                event.phase,
+                                event.variant_id
+                            );
+                        })
+                    };
+                    match result {
+                        Ok(report) => {
+                            print_report(&report);
+                            if let Some(report_path) = report_path {
+                                export_report(&report, report_path);
+                            }
+                        }
                         Err(e) => eprintln!("Analysis failed: {}", e),
                     }
                 }
@@ -428,19 +803,35 @@ fn run_scenario_from_file(path: &PathBuf) {
     }
 }
 
-fn validate_config(path: &PathBuf) {
+/// Loads a scenario file and checks it can run, returning the process exit code.
+fn validate_config(path: &PathBuf) -> i32 {
     println!("Validating config: {}", path.display());
     let settings = Config::builder()
         .add_source(File::from(path.clone()))
         .build();
 
-    match settings {
-        Ok(_) => println!("Config file is valid."),
-        Err(e) => eprintln!("Config file is invalid: {}", e),
+    let scenario = match settings.and_then(|config| config.try_deserialize::<AnalysisScenario>()) {
+        Ok(scenario) => scenario,
+        Err(e) => {
+            eprintln!("Config file is invalid: {}", e);
+            return 1;
+        }
+    };
+
+    let problems = scenario.validate(&default_templates());
+    if problems.is_empty() {
+        println!("Scenario `{}` is valid.", scenario.scenario_id);
+        0
+    } else {
+        for problem in &problems {
+            eprintln!("error: {}", problem);
+        }
+        eprintln!("{} problem(s) found.", problems.len());
+        1
     }
 }
 
-fn run_demo_scenario() {
+fn run_demo_scenario(report_path: Option<&Path>) {
     // Define a sample scenario
     let base_pdf_path = PathBuf::from("examples/clean_resume.pdf");
     ensure_demo_pdf(&base_pdf_path);
@@ -457,16 +848,19 @@ fn run_demo_scenario() {
                     content: Default::default(),
                 },
                 template_id: "soft_bias".into(),
+                pipeline: None,
             },
             // Plan 2: Aggressive override with padding noise
             AnalysisPlan {
                 profile: ProfileConfig::PaddingNoise {
-                    padding_tokens_before: 256,
-                    padding_tokens_after: 256,
+                    padding_tokens_before: 0,
+                    padding_tokens_after: 0,
+                    intensity: Intensity::Medium,
                     padding_style: PaddingStyle::JobRelated,
                     content: Default::default(),
                 },
                 template_id: "aggressive_override".into(),
+                pipeline: None,
             },
             // Plan 3: Inline job ad injection
             AnalysisPlan {
@@ -477,15 +871,37 @@ fn run_demo_scenario() {
                     content: Default::default(),
                 },
                 template_id: "override_conflict".into(),
+                pipeline: None,
             },
         ],
+        // Stacked attack: low-visibility block plus metadata injection in one variant
+        composite_plans: vec![CompositePlan {
+            profiles: vec![
+                ProfileConfig::LowVisibilityBlock {
+                    font_size_min: 2,
+                    font_size_max: 4,
+                    color_profile: LowVisibilityPalette::OffWhite,
+                    content: Default::default(),
+                },
+                ProfileConfig::StructuralFields {
+                    targets: vec![StructuralTarget::PdfTag],
+                },
+            ],
+            template_id: "soft_bias".into(),
+            pipeline: None,
+        }],
         // Configure the target pipeline (simulated)
         pipeline: PipelineConfig {
             pipeline_type: PipelineType::LocalPrompt {
                 model: Some("local-sim".into()),
                 prompt_template: None,
+                scoring: None,
             },
             target: Some("local_simulation".into()),
+            response_score_field: None,
+            response_label_field: None,
+            timeout_secs: None,
+            max_retries: None,
         },
         // Define metrics to track
         metrics: vec![
@@ -498,21 +914,45 @@ fn run_demo_scenario() {
         // Configure logging
         logging: Some(LoggingConfig {
             capture: vec![LogField::PdfVariantHash, LogField::RawLlmResponse],
+            path: None,
         }),
+        capture_text_diff: false,
     };
 
     // Initialize the engine with default templates
-    let engine = AnalysisEngine::new(default_templates());
+    let engine = AnalysisEngine::new(default_templates()).with_app_config(AppConfig::load());
 
     println!("Starting Demo Analysis Scenario: {}", scenario.scenario_id);
 
     // Run the scenario
     match engine.run_scenario(&scenario) {
-        Ok(report) => print_report(&report),
+        Ok(report) => {
+            print_report(&report);
+            if let Some(report_path) = report_path {
+                export_report(&report, report_path);
+            }
+        }
         Err(e) => eprintln!("Scenario failed: {}", e),
     }
 }
 
+/// Writes the report as CSV, HTML or JSON depending on the file extension.
+fn export_report(report: &superpoweredcv::analysis::ScenarioReport, path: &Path) {
+    let extension = path
+        .extension()
+        .map(|ext| ext.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    let result = match extension.as_str() {
+        "csv" => report.write_csv(path),
+        "html" | "htm" => std::fs::write(path, report.to_html()).map_err(Into::into),
+        _ => report.write_json(path),
+    };
+    match result {
+        Ok(()) => println!("Report written to {}", path.display()),
+        Err(e) => eprintln!("Failed to write report: {}", e),
+    }
+}
+
 fn print_report(report: &superpoweredcv::analysis::ScenarioReport) {
     println!("Scenario completed successfully!");
     println!("Report ID: {}", report.scenario_id);
@@ -536,6 +976,14 @@ fn print_report(report: &superpoweredcv::analysis::ScenarioReport) {
         }
         println!("");
     }
+
+    let summary = report.summary();
+    let delta = |delta: Option<f64>| delta.map(|d| format!("{:+.2}", d)).unwrap_or_else(|| "-".into());
+    println!("Summary:");
+    println!("  Score increased: {}/{}", summary.score_increased, summary.variants);
+    println!("  Max delta: {}  Mean delta: {}", delta(summary.max_delta), delta(summary.mean_delta));
+    println!("  Classification flips: {}", summary.classification_flips);
+    println!("  Most effective profile: {}", summary.most_effective_profile.as_deref().unwrap_or("-"));
 }
 
 fn ensure_demo_pdf(path: &PathBuf) {
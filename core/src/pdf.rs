@@ -1,18 +1,53 @@
-use crate::attacks::{ProfileConfig, InjectionPosition, LowVisibilityPalette, OffpageOffset, InjectionContent};
+use crate::attacks::{ProfileConfig, InjectionPosition, LowVisibilityPalette, InjectionContent, ObfuscationMode, PhraseSelection};
 use crate::attacks::templates::InjectionTemplate;
-use crate::Result;
-use crate::pdf_utils;
+use crate::{PdfLoadError, Result};
+use crate::pdf_utils::{self, FillColor, InjectionRecord};
 use lopdf::{Document, Object, StringFormat, dictionary};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
 use std::fs;
-use std::path::PathBuf;
+use std::io::Cursor;
+use std::path::{Path, PathBuf};
 use uuid::Uuid;
 
+mod cache;
+pub mod divergence;
+pub mod filename;
+pub mod sanitizer;
+pub mod scanner;
+
+pub use divergence::{visible_vs_extracted, DivergenceReport, HiddenReason, HiddenRun};
+pub use filename::{render_filename, utc_date_today, FilenameParts};
+pub use sanitizer::{sanitize, SanitizeReport};
+pub use scanner::{risk_score, scan_for_injections, scan_path, scan_summary, DetectionFinding, FileScan, FindingCategory, ScanSummary};
+
+/// `base_pdf` value that mutates a fresh blank page instead of a file.
+pub const BLANK_BASE_PDF: &str = "<blank>";
+
+/// Placeholder in a tracking pixel URL replaced with the variant's ID.
+pub const VARIANT_ID_PLACEHOLDER: &str = "{variant_id}";
+
+/// Whether `path` asks for a blank base page: empty or [`BLANK_BASE_PDF`].
+pub fn is_blank_base(path: &Path) -> bool {
+    path.as_os_str().is_empty() || path.as_os_str() == BLANK_BASE_PDF
+}
+
+/// Reads a base PDF, reporting a missing file as [`PdfLoadError::NotFound`]
+/// like the loaders do.
+fn read_base_pdf(path: &Path) -> Result<Vec<u8>> {
+    fs::read(path).map_err(|e| match e.kind() {
+        std::io::ErrorKind::NotFound => PdfLoadError::NotFound.into(),
+        _ => e.into(),
+    })
+}
+
 /// Request to mutate a PDF with a specific analysis profile and template.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PdfMutationRequest {
-    /// Path to the base PDF file.
+    /// Path to the base PDF file. An empty path or [`BLANK_BASE_PDF`]
+    /// starts from a one-page blank document instead.
     pub base_pdf: PathBuf,
     /// Configuration for the analysis profiles (multiple allowed).
     pub profiles: Vec<ProfileConfig>,
@@ -20,6 +55,52 @@ pub struct PdfMutationRequest {
     pub template: InjectionTemplate,
     /// Optional ID for the variant.
     pub variant_id: Option<String>,
+    /// Seed for every randomized injection choice. The same request and seed
+    /// always yield the same injected text; lopdf's object serialization is
+    /// not covered, so the output bytes may still differ. Drawn from entropy
+    /// (and recorded in the notes) when absent.
+    #[serde(default)]
+    pub seed: Option<u64>,
+    /// Text stamped diagonally across page 1 so the variant is visibly
+    /// marked as a test artifact.
+    #[serde(default)]
+    pub watermark: Option<String>,
+    /// Append the injected objects as a PDF incremental update instead of
+    /// rewriting the file, so the base PDF's bytes are kept verbatim.
+    #[serde(default)]
+    pub incremental: bool,
+    /// Password used to open an encrypted base PDF.
+    #[serde(default)]
+    pub password: Option<String>,
+    /// Encrypts the output with this password. Without it the output is
+    /// saved unencrypted, even when the base PDF was encrypted.
+    #[serde(default)]
+    pub output_password: Option<String>,
+    /// Deflates uncompressed streams and marks them `/FlateDecode` before
+    /// saving, shrinking the file and keeping injected page text out of the
+    /// raw bytes (the Info dictionary marker is not a stream and stays
+    /// readable). Incremental updates only compress the streams they append.
+    #[serde(default)]
+    pub compress: bool,
+}
+
+impl Default for PdfMutationRequest {
+    /// A request with no profiles against a blank base page, using the
+    /// built-in default template and every option off.
+    fn default() -> Self {
+        Self {
+            base_pdf: PathBuf::new(),
+            profiles: Vec::new(),
+            template: crate::api::default_template(),
+            variant_id: None,
+            seed: None,
+            watermark: None,
+            incremental: false,
+            password: None,
+            output_password: None,
+            compress: false,
+        }
+    }
 }
 
 /// Result of a PDF mutation operation.
@@ -33,18 +114,42 @@ pub struct PdfMutationResult {
     pub variant_hash: Option<String>,
     /// Notes or logs from the mutation process.
     pub notes: Vec<String>,
+    /// Whether the requested watermark was stamped onto the output.
+    #[serde(default)]
+    pub watermark_applied: bool,
 }
 
-/// Trait for components that can mutate PDFs.
-pub trait PdfMutator {
-    /// Mutates a PDF based on the request.
+/// Trait for components that can mutate a base document, whatever its format
+/// (see [`crate::docx::DocxMutator`] for the DOCX implementation).
+pub trait DocumentMutator {
+    /// Mutates a document based on the request.
     fn mutate(&self, request: PdfMutationRequest) -> Result<PdfMutationResult>;
+
+    /// IDs of the profiles this mutator actually applies (see
+    /// [`ProfileConfig::id`]). Other profiles are skipped with a note or
+    /// rejected, depending on the implementation.
+    fn supported_profiles(&self) -> &[&'static str];
+
+    /// Returns true when `profile` is in [`Self::supported_profiles`].
+    fn supports(&self, profile: &ProfileConfig) -> bool {
+        self.supported_profiles().contains(&profile.id())
+    }
 }
 
+/// The original name of [`DocumentMutator`], from when only PDFs were supported.
+pub use DocumentMutator as PdfMutator;
+
 /// A real PDF mutator that uses lopdf to modify PDF files.
 pub struct RealPdfMutator {
     /// Directory where mutated PDFs will be saved.
     pub output_dir: PathBuf,
+    /// File name template for saved variants, see [`render_filename`].
+    /// `<variant_id>.pdf` when unset. Templates without `{variant_id}` or
+    /// `{hash8}` get `_{variant_id}` appended so variants cannot overwrite
+    /// each other.
+    pub filename_template: Option<String>,
+    /// Content-addressed cache of saved variants, see [`with_cache`](Self::with_cache).
+    pub cache_dir: Option<PathBuf>,
 }
 
 impl RealPdfMutator {
@@ -52,201 +157,557 @@ impl RealPdfMutator {
     pub fn new(output_dir: impl Into<PathBuf>) -> Self {
         RealPdfMutator {
             output_dir: output_dir.into(),
+            filename_template: None,
+            cache_dir: None,
         }
     }
+
+    /// Names saved variants from `template`, e.g. `{variant_id}_{date}_{hash8}`.
+    pub fn with_filename_template(mut self, template: impl Into<String>) -> Self {
+        self.filename_template = Some(template.into());
+        self
+    }
+
+    /// Caches variants in `dir`, keyed by a hash of the base PDF's bytes and
+    /// the request. A request already in the cache is hardlinked (or copied)
+    /// to the output path instead of being mutated again. Only seeded
+    /// requests are cached, as others are not deterministic; files a profile
+    /// refers to (e.g. `AppendPage`) are keyed by path, not content.
+    ///
+    /// Safe to share between threads and processes: entries are published
+    /// with atomic renames. Outputs may share storage with the cache, so
+    /// edit them only by replacing the file.
+    pub fn with_cache(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.cache_dir = Some(dir.into());
+        self
+    }
 }
 
 impl PdfMutator for RealPdfMutator {
     fn mutate(&self, request: PdfMutationRequest) -> Result<PdfMutationResult> {
-        fs::create_dir_all(&self.output_dir)?;
-
         let variant_id = request
             .variant_id
+            .clone()
             .unwrap_or_else(|| Uuid::new_v4().to_string());
+        let request = PdfMutationRequest { variant_id: Some(variant_id.clone()), ..request };
+        let cache = match &self.cache_dir {
+            Some(dir) => cache::key(&request)?.map(|key| (dir, key)),
+            None => None,
+        };
+        let cached = match &cache {
+            Some((dir, key)) => cache::load(dir, key)?,
+            None => None,
+        };
+        let mutated = match &cached {
+            Some(cached) => MutatedPdfBytes {
+                bytes: Vec::new(),
+                variant_hash: cached.variant_hash.clone(),
+                notes: cached.notes.clone(),
+                watermark_applied: cached.watermark_applied,
+            },
+            None => {
+                let mut mutated = mutate_to_bytes(&request)?;
+                match &cache {
+                    Some((dir, key)) => cache::store(dir, key, &mutated)?,
+                    None if self.cache_dir.is_some() => {
+                        mutated.notes.push("Output cache skipped: the request has no seed".to_string())
+                    }
+                    None => {}
+                }
+                mutated
+            }
+        };
 
-        let file_name = format!("{}.pdf", variant_id);
+        let file_name = match &self.filename_template {
+            Some(template) => {
+                let template = if template.contains("{variant_id}") || template.contains("{hash8}") {
+                    template.clone()
+                } else {
+                    format!("{}_{{variant_id}}", template.strip_suffix(".pdf").unwrap_or(template))
+                };
+                let profiles: Vec<String> = request.profiles.iter().map(|p| p.id().to_string()).collect();
+                render_filename(
+                    &template,
+                    &FilenameParts {
+                        variant_id: &variant_id,
+                        profiles: &profiles,
+                        template: &request.template.id,
+                        hash: Some(&mutated.variant_hash),
+                        date: &filename::utc_date_today(),
+                        ..Default::default()
+                    },
+                )
+            }
+            None => format!("{}.pdf", variant_id),
+        };
+
+        fs::create_dir_all(&self.output_dir)?;
         let output_path = self.output_dir.join(file_name);
+        let mut notes = mutated.notes;
+        match &cached {
+            Some(cached) => {
+                cache::link_or_copy(&cached.path, &output_path)?;
+                notes.push(format!("Reused cached variant {}", &mutated.variant_hash[..8]));
+            }
+            None => {
+                if self.cache_dir.is_some() {
+                    cache::remove_if_present(&output_path)?;
+                }
+                fs::write(&output_path, &mutated.bytes)?;
+            }
+        }
+
+        Ok(PdfMutationResult {
+            variant_id,
+            mutated_pdf: output_path,
+            variant_hash: Some(mutated.variant_hash),
+            notes,
+            watermark_applied: mutated.watermark_applied,
+        })
+    }
+
+    fn supported_profiles(&self) -> &[&'static str] {
+        ProfileConfig::ALL_IDS
+    }
+}
+
+/// A mutated PDF held in memory.
+#[derive(Debug, Clone)]
+pub struct MutatedPdfBytes {
+    /// The serialized PDF.
+    pub bytes: Vec<u8>,
+    /// SHA-256 of `bytes`, hex encoded.
+    pub variant_hash: String,
+    /// Notes or logs from the mutation process.
+    pub notes: Vec<String>,
+    /// Whether the requested watermark was stamped onto the output.
+    pub watermark_applied: bool,
+}
 
-        // Load the base PDF
-        let mut doc = Document::load(&request.base_pdf)
-            .map_err(|e| crate::AnalysisError::PdfError(format!("Failed to load PDF: {}", e)))?;
+/// Applies the request's profiles to the base PDF and returns the result as
+/// bytes, without touching the filesystem beyond reading `base_pdf`. A base
+/// PDF without pages is rejected with a single `PdfError`.
+pub fn mutate_to_bytes(request: &PdfMutationRequest) -> Result<MutatedPdfBytes> {
+    // Load the base PDF
+    let original = if is_blank_base(&request.base_pdf) {
+        let mut blank = Vec::new();
+        pdf_utils::create_blank_pdf().save_to(&mut blank)?;
+        blank
+    } else {
+        read_base_pdf(&request.base_pdf)?
+    };
+    let (mut doc, encrypted) = pdf_utils::load_document(&original, request.password.as_deref())?;
+    if request.incremental && (encrypted || request.output_password.is_some()) {
+        return Err(crate::AnalysisError::PdfError(
+            "incremental updates are not supported for encrypted PDFs".to_string(),
+        ));
+    }
+    // Every injection targets a page; fail once here rather than per profile.
+    let page_count = doc.get_pages().len();
+    if page_count == 0 {
+        return Err(crate::AnalysisError::PdfError("document has no pages".to_string()));
+    }
 
-        let mut notes = Vec::new();
-        let default_text = &request.template.text_template;
-        let mut final_injected_text = default_text.clone();
+    // Incremental updates append only changed objects, so streams from the
+    // base must stay as they are.
+    let base_objects = (request.compress && request.incremental).then(|| doc.objects.clone());
 
-        for profile in &request.profiles {
-            match profile {
-                ProfileConfig::VisibleMetaBlock { position, intensity: _, content } => {
-                    let text_to_inject = get_injection_text(content, default_text);
-                    final_injected_text = text_to_inject.clone();
-                    let (x, y) = match position {
-                        InjectionPosition::Header => (50.0, 800.0),
-                        InjectionPosition::Footer => (50.0, 50.0),
-                        InjectionPosition::Section(_) => (50.0, 400.0), // Default to middle for now
-                    };
-                    // Inject on the first page
-                    pdf_utils::add_text_to_page(&mut doc, 1, &text_to_inject, x, y, 10.0, 0.0)?;
-                    notes.push(format!("Injected visible block at {:?} ({}, {})", position, x, y));
-                }
-                ProfileConfig::LowVisibilityBlock { font_size_min, color_profile, content, .. } => {
-                    let text_to_inject = get_injection_text(content, default_text);
-                    final_injected_text = text_to_inject.clone();
-                    let gray_level = match color_profile {
-                        LowVisibilityPalette::Gray => 0.95,
-                        LowVisibilityPalette::LightBlue => 0.90, // Simplified to gray for now
-                        LowVisibilityPalette::OffWhite => 0.99,
-                    };
-                    // Inject at bottom
-                    pdf_utils::add_text_to_page(&mut doc, 1, &text_to_inject, 50.0, 20.0, *font_size_min as f64, gray_level)?;
-                    notes.push(format!("Injected low visibility block (size: {}, gray: {})", font_size_min, gray_level));
+    let mut notes = Vec::new();
+    if is_blank_base(&request.base_pdf) {
+        notes.push("Started from a blank one-page base PDF".to_string());
+    }
+    if encrypted {
+        notes.push("Decrypted password-protected base PDF".to_string());
+    }
+    let seed = request_seed(request, &mut notes);
+    let mut rng = StdRng::seed_from_u64(seed);
+    let default_text = &request.template.text_template;
+    let mut final_injected_text = default_text.clone();
+    let mut injections = Vec::new();
+    if request.profiles.iter().any(fragments_text) {
+        notes.push("Fragmented injected text into one Tj per word".to_string());
+    }
+    if page_count > REPEAT_PAGE_WARNING && request.profiles.iter().any(repeats_per_page) {
+        notes.push(format!(
+            "Warning: repeating injected blocks on all {} pages (more than {}) inflates the output",
+            page_count, REPEAT_PAGE_WARNING
+        ));
+    }
+
+    for profile in &request.profiles {
+        match profile {
+            ProfileConfig::VisibleMetaBlock { position, intensity: _, content } => {
+                let text_to_inject = get_injection_text(content, default_text, seed, &mut rng, &mut notes);
+                final_injected_text = text_to_inject.clone();
+                let (page_number, x, y) = match position {
+                    InjectionPosition::Header => (1, 50.0, 800.0),
+                    InjectionPosition::Footer => (1, 50.0, 50.0),
+                    InjectionPosition::Section(name) => match pdf_utils::find_section_heading(&doc, name)? {
+                        // In the gap under the heading, above the section's first line.
+                        Some((page_number, heading)) => (page_number, heading.x, heading.y - heading.font_size * 0.75),
+                        None => {
+                            notes.push(format!("Section '{}' not found; fell back to the middle of page 1", name));
+                            (1, 50.0, 400.0)
+                        }
+                    },
+                };
+                let pages = if let InjectionPosition::Section(_) = position {
+                    if content.repeat_per_page {
+                        notes.push("Section blocks are not repeated per page; injected under the heading only".to_string());
+                    }
+                    vec![page_number]
+                } else {
+                    target_pages(&doc, content, page_number)
+                };
+                let max_width = wrap_width(&doc, x);
+                let y = match position {
+                    InjectionPosition::Footer => pdf_utils::bottom_anchored_y(&text_to_inject, y, 10.0, max_width),
+                    _ => y,
+                };
+                for &page_number in &pages {
+                    draw_text(&mut doc, content.fragment, page_number, &text_to_inject, x, y, 10.0, 0.0, max_width)?;
+                    injections.push(InjectionRecord {
+                        page_number,
+                        text: text_to_inject.clone(),
+                        x,
+                        y,
+                        font_size: 10.0,
+                        max_width,
+                    });
                 }
-                ProfileConfig::OffpageLayer { offset_strategy, content, .. } => {
-                    let text_to_inject = get_injection_text(content, default_text);
-                    final_injected_text = text_to_inject.clone();
-                    let (x, y) = match offset_strategy {
-                        OffpageOffset::BottomClip => (50.0, -1000.0),
-                        OffpageOffset::RightClip => (1000.0, 500.0),
-                    };
-                    pdf_utils::add_text_to_page(&mut doc, 1, &text_to_inject, x, y, 1.0, 0.0)?;
-                    notes.push(format!("Injected offpage layer at ({}, {})", x, y));
+                notes.push(format!("Injected visible block at {:?} (page {}, {}, {})", position, describe_pages(&pages), x, y));
+            }
+            ProfileConfig::LowVisibilityBlock { font_size_min, color_profile, content, .. } => {
+                let text_to_inject = get_injection_text(content, default_text, seed, &mut rng, &mut notes);
+                final_injected_text = text_to_inject.clone();
+                let color = match color_profile {
+                    LowVisibilityPalette::Gray => FillColor::Gray(0.95),
+                    LowVisibilityPalette::LightBlue => FillColor::Gray(0.90), // Simplified to gray for now
+                    LowVisibilityPalette::OffWhite => FillColor::Gray(0.99),
+                    LowVisibilityPalette::MatchBackground { bg: (r, g, b), delta } => FillColor::Rgb(
+                        pdf_utils::shade_near([*r as f64, *g as f64, *b as f64], *delta as f64),
+                    ),
+                };
+                // Inject at bottom
+                let font_size = *font_size_min as f64;
+                let max_width = wrap_width(&doc, 50.0);
+                let y = pdf_utils::bottom_anchored_y(&text_to_inject, 20.0, font_size, max_width);
+                for page_number in target_pages(&doc, content, 1) {
+                    draw_text(&mut doc, content.fragment, page_number, &text_to_inject, 50.0, y, font_size, color, max_width)?;
+                    injections.push(InjectionRecord {
+                        page_number,
+                        text: text_to_inject.clone(),
+                        x: 50.0,
+                        y,
+                        font_size,
+                        max_width,
+                    });
                 }
-                ProfileConfig::UnderlayText => {
-                    // Inject text behind existing content (e.g. white text or just first in stream)
-                    // We use a large font size to cover area, but white color so it's invisible to human eye
-                    // but present in stream. Or we can use black text if we are sure it's covered by an image.
-                    // For safety/simplicity, we use white text (invisible) but placed first.
-                    // Actually, spec says "invisible but still selectable".
-                    let text_to_inject = default_text.clone();
-                    final_injected_text = text_to_inject.clone();
-                    pdf_utils::prepend_text_to_page(&mut doc, 1, &text_to_inject, 50.0, 400.0, 12.0, 1.0)?; // 1.0 is white in Gray colorspace
-                    notes.push("Injected underlay text (white, prepended to stream)".to_string());
+                let color = match color {
+                    FillColor::Gray(gray) => format!("gray: {}", gray),
+                    FillColor::Rgb([r, g, b]) => format!("rgb: {:.3} {:.3} {:.3}", r, g, b),
+                };
+                notes.push(format!("Injected low visibility block (size: {}, {})", font_size_min, color));
+            }
+            ProfileConfig::OffpageLayer { offset_strategy, content, .. } => {
+                let text_to_inject = get_injection_text(content, default_text, seed, &mut rng, &mut notes);
+                final_injected_text = text_to_inject.clone();
+                let mut origins = Vec::new();
+                for page_number in target_pages(&doc, content, 1) {
+                    let page_id = pdf_utils::nth_page_id(&doc, page_number)?;
+                    let media_box = pdf_utils::page_media_box(&doc, page_id);
+                    let (x, y) = offset_strategy.origin(media_box);
+                    let width = media_box[2] - RIGHT_MARGIN - x;
+                    let max_width = (width > 0.0).then_some(width);
+                    draw_text(&mut doc, content.fragment, page_number, &text_to_inject, x, y, 1.0, 0.0, max_width)?;
+                    injections.push(InjectionRecord {
+                        page_number,
+                        text: text_to_inject.clone(),
+                        x,
+                        y,
+                        font_size: 1.0,
+                        max_width,
+                    });
+                    if !origins.contains(&(x, y)) {
+                        origins.push((x, y));
+                    }
                 }
-                ProfileConfig::StructuralFields { targets } => {
-                    let text_to_inject = default_text.clone();
-                    final_injected_text = text_to_inject.clone();
-                    
-                    let info_id = match doc.trailer.get(b"Info").ok().and_then(|obj| obj.as_reference().ok()) {
-                        Some(id) => id,
-                        None => {
-                            let info_id = doc.add_object(dictionary! {});
-                            doc.trailer.set("Info", info_id);
-                            info_id
-                        }
-                    };
-
-                    if let Ok(info) = doc.get_object_mut(info_id) {
-                        if let Object::Dictionary(dict) = info {
-                            for target in targets {
-                                match target {
-                                    crate::attacks::StructuralTarget::AltText => {
-                                        // Simulating AltText by adding a custom key, as real AltText requires structure tree
-                                        dict.set("AltTextInjection", Object::String(text_to_inject.clone().into(), StringFormat::Literal));
-                                        notes.push("Injected into Info dict (simulated AltText)".to_string());
-                                    }
-                                    crate::attacks::StructuralTarget::PdfTag => {
-                                        dict.set("Keywords", Object::String(text_to_inject.clone().into(), StringFormat::Literal));
-                                        notes.push("Injected into Keywords".to_string());
-                                    }
-                                    crate::attacks::StructuralTarget::XmpMetadata => {
-                                        dict.set("Subject", Object::String(text_to_inject.clone().into(), StringFormat::Literal));
-                                        notes.push("Injected into Subject".to_string());
-                                    }
+                let origins: Vec<String> = origins.iter().map(|(x, y)| format!("({}, {})", x, y)).collect();
+                notes.push(format!("Injected offpage layer at {}", origins.join(", ")));
+            }
+            ProfileConfig::UnderlayText => {
+                // Inject text behind existing content (e.g. white text or just first in stream)
+                // We use a large font size to cover area, but white color so it's invisible to human eye
+                // but present in stream. Or we can use black text if we are sure it's covered by an image.
+                // For safety/simplicity, we use white text (invisible) but placed first.
+                // Actually, spec says "invisible but still selectable".
+                let text_to_inject = default_text.clone();
+                final_injected_text = text_to_inject.clone();
+                let max_width = wrap_width(&doc, 50.0);
+                pdf_utils::prepend_text_to_page(&mut doc, 1, &text_to_inject, 50.0, 400.0, 12.0, 1.0, max_width)?; // 1.0 is white in Gray colorspace
+                injections.push(InjectionRecord {
+                    page_number: 1,
+                    text: text_to_inject.clone(),
+                    x: 50.0,
+                    y: 400.0,
+                    font_size: 12.0,
+                    max_width,
+                });
+                notes.push("Injected underlay text (white, prepended to stream)".to_string());
+            }
+            ProfileConfig::StructuralFields { targets } => {
+                let text_to_inject = default_text.clone();
+                final_injected_text = text_to_inject.clone();
+                
+                let info_id = match doc.trailer.get(b"Info").ok().and_then(|obj| obj.as_reference().ok()) {
+                    Some(id) => id,
+                    None => {
+                        let info_id = doc.add_object(dictionary! {});
+                        doc.trailer.set("Info", info_id);
+                        info_id
+                    }
+                };
+
+                if let Ok(info) = doc.get_object_mut(info_id) {
+                    if let Object::Dictionary(dict) = info {
+                        for target in targets {
+                            match target {
+                                crate::attacks::StructuralTarget::AltText => {
+                                    // Simulating AltText by adding a custom key, as real AltText requires structure tree
+                                    dict.set("AltTextInjection", Object::String(text_to_inject.clone().into(), StringFormat::Literal));
+                                    notes.push("Injected into Info dict (simulated AltText)".to_string());
+                                }
+                                crate::attacks::StructuralTarget::PdfTag => {
+                                    dict.set("Keywords", Object::String(text_to_inject.clone().into(), StringFormat::Literal));
+                                    notes.push("Injected into Keywords".to_string());
+                                }
+                                crate::attacks::StructuralTarget::XmpMetadata => {
+                                    dict.set("Subject", Object::String(text_to_inject.clone().into(), StringFormat::Literal));
+                                    notes.push("Injected into Subject".to_string());
                                 }
                             }
                         }
                     }
                 }
-                ProfileConfig::PaddingNoise { padding_tokens_before, padding_tokens_after, padding_style, content } => {
-                    let noise_before = generate_noise(Some(*padding_tokens_before as u32), None, padding_style);
-                    let noise_after = generate_noise(None, Some(*padding_tokens_after as u32), padding_style);
-                    let text_to_inject = get_injection_text(content, default_text);
-                    
-                    let full_text = format!("{} {} {}", noise_before, text_to_inject, noise_after);
-                    final_injected_text = full_text.clone();
-                    
-                    // Inject as low visibility text at the end
-                    pdf_utils::add_text_to_page(&mut doc, 1, &full_text, 50.0, 10.0, 1.0, 0.99)?;
-                    notes.push(format!("Injected padding noise ({:?}) with content", padding_style));
+            }
+            ProfileConfig::PaddingNoise { padding_tokens_before, padding_tokens_after, intensity, padding_style, content } => {
+                let (before, after) = if *padding_tokens_before == 0 && *padding_tokens_after == 0 {
+                    let tokens = intensity.default_padding_tokens();
+                    notes.push(format!("Using {:?} padding of {} tokens per side", intensity, tokens));
+                    (tokens, tokens)
+                } else {
+                    (*padding_tokens_before, *padding_tokens_after)
+                };
+                let noise_before = generate_noise(&mut rng, Some(before as u32), None, padding_style);
+                let noise_after = generate_noise(&mut rng, None, Some(after as u32), padding_style);
+                let text_to_inject = get_injection_text(content, default_text, seed, &mut rng, &mut notes);
+                
+                let full_text = format!("{} {} {}", noise_before, text_to_inject, noise_after);
+                final_injected_text = full_text.clone();
+                
+                // Inject as low visibility text at the end
+                let max_width = wrap_width(&doc, 50.0);
+                let y = pdf_utils::bottom_anchored_y(&full_text, 10.0, 1.0, max_width);
+                for page_number in target_pages(&doc, content, 1) {
+                    draw_text(&mut doc, content.fragment, page_number, &full_text, 50.0, y, 1.0, 0.99, max_width)?;
+                    injections.push(InjectionRecord {
+                        page_number,
+                        text: full_text.clone(),
+                        x: 50.0,
+                        y,
+                        font_size: 1.0,
+                        max_width,
+                    });
+                }
+                notes.push(format!("Injected padding noise ({:?}) with content", padding_style));
+            }
+            ProfileConfig::InlineJobAd { job_ad_source, placement, ad_excerpt_ratio, content } => {
+                if *ad_excerpt_ratio < 1.0 {
+                    notes.push(format!("ad_excerpt_ratio {} is not applied; injected the whole job ad", ad_excerpt_ratio));
                 }
-                ProfileConfig::InlineJobAd { job_ad_source, placement, ad_excerpt_ratio: _, content } => {
-                    let ad_text = match job_ad_source {
-                        crate::attacks::JobAdSource::Inline => "Senior Software Engineer required. Must have Rust experience.".to_string(), // Placeholder
-                        _ => "Job Ad Content Placeholder".to_string(),
-                    };
-                    let text_to_inject = get_injection_text(content, default_text);
-                    let full_text = format!("{} {}", text_to_inject, ad_text);
-                    final_injected_text = full_text.clone();
-                    
-                    let (x, y) = match placement {
-                        crate::attacks::JobAdPlacement::Front => (50.0, 800.0),
-                        crate::attacks::JobAdPlacement::Back => (50.0, 50.0),
-                        _ => (50.0, 50.0),
-                    };
-                    
-                    // Inject as visible text (or low vis depending on intent, assuming visible for now based on name)
-                    // Spec says "Inline Job Ad", usually implies visible or hidden. Let's assume hidden/low-vis for red-teaming context usually,
-                    // but "Inline" might mean visible. Let's use small white text for safety in this context.
-                    pdf_utils::add_text_to_page(&mut doc, 1, &full_text, x, y, 4.0, 0.95)?;
-                    notes.push(format!("Injected inline job ad ({:?}) with content", placement));
+                let ad_text = match job_ad_source {
+                    crate::attacks::JobAdSource::Inline => "Senior Software Engineer required. Must have Rust experience.".to_string(), // Placeholder
+                    _ => "Job Ad Content Placeholder".to_string(),
+                };
+                let text_to_inject = get_injection_text(content, default_text, seed, &mut rng, &mut notes);
+                let full_text = format!("{} {}", text_to_inject, ad_text);
+                final_injected_text = full_text.clone();
+                
+                let (x, y) = match placement {
+                    crate::attacks::JobAdPlacement::Front => (50.0, 800.0),
+                    crate::attacks::JobAdPlacement::Back => (50.0, 50.0),
+                    _ => (50.0, 50.0),
+                };
+                
+                // Inject as visible text (or low vis depending on intent, assuming visible for now based on name)
+                // Spec says "Inline Job Ad", usually implies visible or hidden. Let's assume hidden/low-vis for red-teaming context usually,
+                // but "Inline" might mean visible. Let's use small white text for safety in this context.
+                let max_width = wrap_width(&doc, x);
+                let y = match placement {
+                    crate::attacks::JobAdPlacement::Front => y,
+                    _ => pdf_utils::bottom_anchored_y(&full_text, y, 4.0, max_width),
+                };
+                for page_number in target_pages(&doc, content, 1) {
+                    draw_text(&mut doc, content.fragment, page_number, &full_text, x, y, 4.0, 0.95, max_width)?;
+                    injections.push(InjectionRecord {
+                        page_number,
+                        text: full_text.clone(),
+                        x,
+                        y,
+                        font_size: 4.0,
+                        max_width,
+                    });
                 }
-                ProfileConfig::TrackingPixel { url } => {
-                    // Inject a URI Action on a Link Annotation (invisible rectangle)
-                    // This is the most reliable way to trigger a network request on click, 
-                    // but for "open" tracking, we might try an external XObject or just a link covering the whole page.
-                    // Here we add a link covering the top of the page.
-                    pdf_utils::add_link_annotation(&mut doc, 1, url, 0.0, 0.0, 600.0, 850.0)?;
+                notes.push(format!("Injected inline job ad ({:?}) with content", placement));
+            }
+            ProfileConfig::TrackingPixel { url, cover_whole_page } => {
+                let url = match &request.variant_id {
+                    Some(variant_id) => url.replace(VARIANT_ID_PLACEHOLDER, &encode_uri_component(variant_id)),
+                    None => {
+                        if url.contains(VARIANT_ID_PLACEHOLDER) {
+                            notes.push(format!("Tracking URL keeps {}: the request has no variant ID", VARIANT_ID_PLACEHOLDER));
+                        }
+                        url.clone()
+                    }
+                };
+                // Inject a URI Action on a Link Annotation (invisible rectangle)
+                // This is the most reliable way to trigger a network request on click, 
+                // but for "open" tracking, we might try an external XObject or just a link covering the whole page.
+                if *cover_whole_page {
+                    pdf_utils::add_link_annotation(&mut doc, 1, &url, 0.0, 0.0, 600.0, 850.0)?;
                     notes.push(format!("Injected tracking link (covering page) to {}", url));
+                } else {
+                    let [x_min, _, _, y_max] = pdf_utils::page_media_box(&doc, pdf_utils::nth_page_id(&doc, 1)?);
+                    pdf_utils::add_link_annotation(&mut doc, 1, &url, x_min, y_max - 1.0, 1.0, 1.0)?;
+                    notes.push(format!("Injected tracking link (1x1 at top-left) to {}", url));
                 }
-                ProfileConfig::CodeInjection { payload } => {
-                    // Inject JavaScript Action into the OpenAction of the PDF
-                    pdf_utils::add_javascript_action(&mut doc, payload)?;
-                    notes.push("Injected JavaScript OpenAction".to_string());
+            }
+            ProfileConfig::CodeInjection { payload } => {
+                // Inject JavaScript Action into the OpenAction of the PDF
+                pdf_utils::add_javascript_action(&mut doc, payload)?;
+                notes.push("Injected JavaScript OpenAction".to_string());
+            }
+            ProfileConfig::EmbeddedFile { filename, data } => {
+                let bytes = data.as_bytes();
+                pdf_utils::add_embedded_file(&mut doc, filename, bytes)?;
+                notes.push(format!("Embedded file attachment {} ({} bytes)", filename, bytes.len()));
+            }
+            ProfileConfig::HiddenLayer { content } => {
+                let text_to_inject = get_injection_text(content, default_text, seed, &mut rng, &mut notes);
+                final_injected_text = text_to_inject.clone();
+                let max_width = wrap_width(&doc, 50.0);
+                let pages = target_pages(&doc, content, 1);
+                pdf_utils::add_hidden_ocg_text(&mut doc, &pages, &text_to_inject, 50.0, 400.0, 10.0, max_width, content.fragment)?;
+                for &page_number in &pages {
+                    injections.push(InjectionRecord {
+                        page_number,
+                        text: text_to_inject.clone(),
+                        x: 50.0,
+                        y: 400.0,
+                        font_size: 10.0,
+                        max_width,
+                    });
                 }
+                notes.push(format!("Injected text into hidden optional content layer (page {})", describe_pages(&pages)));
             }
-        }
-        
-        // Always inject metadata as a backup/marker
-        let info_id = match doc.trailer.get(b"Info").ok().and_then(|obj| obj.as_reference().ok()) {
-            Some(id) => id,
-            None => {
-                let info_id = doc.add_object(dictionary! {});
-                doc.trailer.set("Info", info_id);
-                info_id
+            ProfileConfig::FormField { field_name, value } => {
+                pdf_utils::add_acroform_text_field(&mut doc, 1, field_name, value, [0.0, 0.0, 0.0, 0.0])?;
+                notes.push(format!("Injected hidden form field `{}`", field_name));
             }
-        };
-
-        if let Ok(info) = doc.get_object_mut(info_id) {
-            if let Object::Dictionary(dict) = info {
-                dict.set(
-                    "CustomInjection", 
-                    Object::String(final_injected_text.into(), StringFormat::Literal)
-                );
-                dict.set(
-                    "Producer",
-                    Object::String("SuperpoweredCV Analysis Tool".into(), StringFormat::Literal)
-                );
+            ProfileConfig::AnnotationText { content, opacity } => {
+                let text_to_inject = get_injection_text(content, default_text, seed, &mut rng, &mut notes);
+                final_injected_text = text_to_inject.clone();
+                if content.fragment {
+                    notes.push("FreeText annotations hold their text whole; fragment is not applied".to_string());
+                }
+                let pages = target_pages(&doc, content, 1);
+                for &page_number in &pages {
+                    pdf_utils::add_freetext_annotation(&mut doc, page_number, &text_to_inject, [50.0, 720.0, 545.0, 760.0], *opacity as f64)?;
+                }
+                notes.push(format!("Injected FreeText annotation (page {}, opacity: {})", describe_pages(&pages), opacity));
+            }
+            ProfileConfig::RemoteAction { kind, target } => {
+                pdf_utils::add_document_action(&mut doc, *kind, target)?;
+                notes.push(format!("Injected {} OpenAction targeting {}", kind.subtype(), target));
+            }
+            ProfileConfig::AppendPage { source_pdf } => {
+                let appended = pdf_utils::append_pages_from(&mut doc, source_pdf)?;
+                notes.push(format!("Appended {} page(s) from {}", appended, source_pdf.display()));
             }
         }
+    }
+    
+    if !injections.is_empty() {
+        let coverage = pdf_utils::injection_coverage(&doc, &injections);
+        notes.push(format!("Injected text covers {:.1}% of the page", coverage * 100.0));
+    }
 
-        // Save the mutated PDF
-        let mut file = fs::File::create(&output_path)?;
-        doc.save_to(&mut file)
-            .map_err(|e| crate::AnalysisError::PdfError(format!("Failed to save PDF: {}", e)))?;
+    let watermark_applied = match &request.watermark {
+        Some(text) => {
+            pdf_utils::add_watermark(&mut doc, 1, text)?;
+            notes.push(format!("Stamped watermark \"{}\" on page 1", text));
+            true
+        }
+        None => false,
+    };
 
-        // Calculate hash of the new file
-        let mut hasher = Sha256::new();
-        let content = fs::read(&output_path)?;
-        hasher.update(&content);
-        let hash = format!("{:x}", hasher.finalize());
+    // Always inject metadata as a backup/marker
+    let info_id = match doc.trailer.get(b"Info").ok().and_then(|obj| obj.as_reference().ok()) {
+        Some(id) => id,
+        None => {
+            let info_id = doc.add_object(dictionary! {});
+            doc.trailer.set("Info", info_id);
+            info_id
+        }
+    };
 
-        Ok(PdfMutationResult {
-            variant_id,
-            mutated_pdf: output_path,
-            variant_hash: Some(hash),
-            notes,
-        })
+    if let Ok(info) = doc.get_object_mut(info_id) {
+        if let Object::Dictionary(dict) = info {
+            dict.set(
+                "CustomInjection", 
+                Object::String(final_injected_text.into(), StringFormat::Literal)
+            );
+            dict.set(
+                "Producer",
+                Object::String("SuperpoweredCV Analysis Tool".into(), StringFormat::Literal)
+            );
+        }
     }
+
+    if request.compress {
+        let compressed = match &base_objects {
+            Some(base) => pdf_utils::compress_streams(&mut doc, |id, object| base.get(&id) == Some(object))?,
+            None => pdf_utils::compress_streams(&mut doc, |_, _| false)?,
+        };
+        notes.push(format!("Compressed {} stream(s) with FlateDecode", compressed));
+    }
+
+    // Serialize the mutated PDF
+    let bytes = if request.incremental {
+        let (bytes, appended) = pdf_utils::incremental_update(original, &doc)?;
+        notes.push(format!("Saved as incremental update ({} objects appended)", appended));
+        bytes
+    } else {
+        if let Some(password) = &request.output_password {
+            pdf_utils::encrypt_document(&mut doc, password)?;
+            notes.push("Encrypted output with AES-256".to_string());
+        }
+        let mut buffer = Cursor::new(Vec::new());
+        doc.save_to(&mut buffer)
+            .map_err(|e| crate::AnalysisError::PdfError(format!("Failed to save PDF: {}", e)))?;
+        buffer.into_inner()
+    };
+
+    let mut hasher = Sha256::new();
+    hasher.update(&bytes);
+    let variant_hash = format!("{:x}", hasher.finalize());
+
+    Ok(MutatedPdfBytes {
+        bytes,
+        variant_hash,
+        notes,
+        watermark_applied,
+    })
 }
 
 /// A placeholder mutator that writes a small marker file containing mutation
@@ -292,27 +753,140 @@ impl PdfMutator for StubPdfMutator {
         hasher.update(&content);
         let hash = format!("{:x}", hasher.finalize());
 
+        let mut notes = vec!["Stub mutator: copied base PDF (or created dummy)".to_string()];
+        for profile in &request.profiles {
+            notes.push(format!("Unsupported profile {}, skipped", profile.id()));
+        }
+
         Ok(PdfMutationResult {
             variant_id,
             mutated_pdf: output_path,
             variant_hash: Some(hash),
-            notes: vec![
-                "Stub mutator: copied base PDF (or created dummy)".into(),
-                format!("Applied profile: {:?}", request.profiles),
-            ],
+            notes,
+            watermark_applied: false,
         })
     }
+
+    fn supported_profiles(&self) -> &[&'static str] {
+        &[]
+    }
+}
+
+/// Right-hand margin kept free when wrapping injected text.
+const RIGHT_MARGIN: f64 = 50.0;
+
+/// Width available to text starting at `x` on the first page, or None when
+/// `x` already lies past the right margin (off-page text is left unwrapped).
+fn wrap_width(doc: &Document, x: f64) -> Option<f64> {
+    let page_id = pdf_utils::nth_page_id(doc, 1).ok()?;
+    let width = pdf_utils::page_media_box(doc, page_id)[2] - RIGHT_MARGIN - x;
+    (width > 0.0).then_some(width)
+}
+
+/// Whether the profile's output depends on the request's variant ID.
+pub(crate) fn embeds_variant_id(profile: &ProfileConfig) -> bool {
+    matches!(profile, ProfileConfig::TrackingPixel { url, .. } if url.contains(VARIANT_ID_PLACEHOLDER))
+}
+
+/// Percent-encodes everything but RFC 3986 unreserved characters.
+fn encode_uri_component(value: &str) -> String {
+    value
+        .bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => (byte as char).to_string(),
+            _ => format!("%{:02X}", byte),
+        })
+        .collect()
+}
+
+/// Page count above which repeating blocks on every page draws a warning note.
+const REPEAT_PAGE_WARNING: usize = 50;
+
+/// Whether the profile draws its text with [`InjectionContent::fragment`] set.
+/// Annotation text lives in the annotation's `/Contents` and is never fragmented.
+pub(crate) fn fragments_text(profile: &ProfileConfig) -> bool {
+    !matches!(profile, ProfileConfig::AnnotationText { .. }) && profile.content().is_some_and(|content| content.fragment)
 }
 
-fn get_injection_text(content: &InjectionContent, default: &str) -> String {
-    if !content.phrases.is_empty() {
-        content.phrases.join("\n")
+/// Whether the profile draws its text with [`InjectionContent::repeat_per_page`] set.
+pub(crate) fn repeats_per_page(profile: &ProfileConfig) -> bool {
+    profile.content().is_some_and(|content| content.repeat_per_page)
+}
+
+/// Every page of `doc` when the content repeats per page, else just `page_number`.
+fn target_pages(doc: &Document, content: &InjectionContent, page_number: u32) -> Vec<u32> {
+    if content.repeat_per_page {
+        doc.get_pages().into_keys().collect()
+    } else {
+        vec![page_number]
+    }
+}
+
+/// `3` for a single page, `1-4` for a run of pages.
+fn describe_pages(pages: &[u32]) -> String {
+    match (pages.first(), pages.last()) {
+        (Some(first), Some(last)) if first != last => format!("{}-{}", first, last),
+        (Some(first), _) => first.to_string(),
+        _ => String::new(),
+    }
+}
+
+/// Draws injected text, one `Tj` per word when `fragment` is set.
+#[allow(clippy::too_many_arguments)]
+fn draw_text(
+    doc: &mut Document,
+    fragment: bool,
+    page_number: u32,
+    text: &str,
+    x: f64,
+    y: f64,
+    font_size: f64,
+    color: impl Into<FillColor>,
+    max_width: Option<f64>,
+) -> Result<()> {
+    if fragment {
+        pdf_utils::add_fragmented_text_to_page(doc, page_number, text, x, y, font_size, color, max_width)
+    } else {
+        pdf_utils::add_text_to_page(doc, page_number, text, x, y, font_size, color, max_width)
+    }
+}
+
+/// The request's seed, or a fresh one from entropy recorded in `notes`.
+pub(crate) fn request_seed(request: &PdfMutationRequest, notes: &mut Vec<String>) -> u64 {
+    match request.seed {
+        Some(seed) => seed,
+        None => {
+            let seed = rand::random();
+            notes.push(format!("Seeded injection RNG from entropy: {}", seed));
+            seed
+        }
+    }
+}
+
+pub(crate) fn get_injection_text(
+    content: &InjectionContent,
+    default: &str,
+    seed: u64,
+    rng: &mut StdRng,
+    notes: &mut Vec<String>,
+) -> String {
+    let phrases = content.selection.select(&content.phrases, seed, rng);
+    if content.selection != PhraseSelection::All && !phrases.is_empty() {
+        let chosen: Vec<&str> = phrases.iter().map(|phrase| phrase.as_str()).collect();
+        notes.push(format!("Selected phrases ({:?}): {}", content.selection, chosen.join(" | ")));
+    }
+    let text = if !phrases.is_empty() {
+        phrases.iter().map(|phrase| phrase.as_str()).collect::<Vec<_>>().join("\n")
     } else {
         default.to_string()
+    };
+    if content.obfuscation != ObfuscationMode::None {
+        notes.push(format!("Applied {:?} obfuscation to injected phrases", content.obfuscation));
     }
+    content.obfuscation.apply(&text)
 }
 
-fn generate_noise(before: Option<u32>, after: Option<u32>, style: &crate::attacks::PaddingStyle) -> String {
+fn generate_noise(rng: &mut StdRng, before: Option<u32>, after: Option<u32>, style: &crate::attacks::PaddingStyle) -> String {
     let count_before = before.unwrap_or(0);
     let count_after = after.unwrap_or(0);
     let total = count_before + count_after;
@@ -321,18 +895,10 @@ fn generate_noise(before: Option<u32>, after: Option<u32>, style: &crate::attack
         return String::new();
     }
 
-    match style {
-        crate::attacks::PaddingStyle::Lorem => {
-            let words = ["lorem", "ipsum", "dolor", "sit", "amet", "consectetur", "adipiscing", "elit"];
-            (0..total).map(|i| words[(i as usize) % words.len()]).collect::<Vec<_>>().join(" ")
-        }
-        crate::attacks::PaddingStyle::ResumeLike => {
-            let words = ["experience", "team", "led", "developed", "managed", "project", "skills", "communication"];
-            (0..total).map(|i| words[(i as usize) % words.len()]).collect::<Vec<_>>().join(" ")
-        }
-        crate::attacks::PaddingStyle::JobRelated => {
-            let words = ["requirements", "qualifications", "responsibilities", "role", "candidate", "apply"];
-            (0..total).map(|i| words[(i as usize) % words.len()]).collect::<Vec<_>>().join(" ")
-        }
-    }
+    let words: &[&str] = match style {
+        crate::attacks::PaddingStyle::Lorem => &["lorem", "ipsum", "dolor", "sit", "amet", "consectetur", "adipiscing", "elit"],
+        crate::attacks::PaddingStyle::ResumeLike => &["experience", "team", "led", "developed", "managed", "project", "skills", "communication"],
+        crate::attacks::PaddingStyle::JobRelated => &["requirements", "qualifications", "responsibilities", "role", "candidate", "apply"],
+    };
+    (0..total).map(|_| words[rng.random_range(0..words.len())]).collect::<Vec<_>>().join(" ")
 }
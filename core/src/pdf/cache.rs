@@ -0,0 +1,115 @@
+use super::{embeds_variant_id, is_blank_base, read_base_pdf, MutatedPdfBytes, PdfMutationRequest};
+use crate::{AnalysisError, Result};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use uuid::Uuid;
+
+/// Everything a cached variant's bytes depend on.
+#[derive(Serialize)]
+struct CacheKey<'a> {
+    version: &'static str,
+    base_pdf: String,
+    request: &'a PdfMutationRequest,
+}
+
+/// What is stored next to a cached PDF to rebuild the mutation result.
+#[derive(Serialize, Deserialize)]
+struct CacheEntry {
+    variant_hash: String,
+    notes: Vec<String>,
+    watermark_applied: bool,
+}
+
+/// A variant found in the cache.
+pub(crate) struct CachedVariant {
+    pub path: PathBuf,
+    pub variant_hash: String,
+    pub notes: Vec<String>,
+    pub watermark_applied: bool,
+}
+
+/// Content hash of a request: the base PDF's bytes plus every request field
+/// except `base_pdf` and, unless a tracking URL embeds it, `variant_id`,
+/// which do not affect the output. `None` without a seed, since the output
+/// is then not deterministic.
+pub(crate) fn key(request: &PdfMutationRequest) -> Result<Option<String>> {
+    if request.seed.is_none() {
+        return Ok(None);
+    }
+    let base_pdf = if is_blank_base(&request.base_pdf) {
+        String::new()
+    } else {
+        format!("{:x}", Sha256::digest(read_base_pdf(&request.base_pdf)?))
+    };
+    let variant_id = request.variant_id.clone().filter(|_| request.profiles.iter().any(embeds_variant_id));
+    let request = PdfMutationRequest { base_pdf: PathBuf::new(), variant_id, ..request.clone() };
+    let key = CacheKey { version: env!("CARGO_PKG_VERSION"), base_pdf, request: &request };
+    let json = serde_json::to_vec(&key).map_err(|e| AnalysisError::JsonError(e.to_string()))?;
+    Ok(Some(format!("{:x}", Sha256::digest(json))))
+}
+
+/// Looks `key` up in `dir`.
+pub(crate) fn load(dir: &Path, key: &str) -> Result<Option<CachedVariant>> {
+    let path = dir.join(format!("{}.pdf", key));
+    if !path.is_file() {
+        return Ok(None);
+    }
+    // The entry is published before the PDF, so it exists once the PDF does.
+    let entry: CacheEntry = match fs::read_to_string(dir.join(format!("{}.json", key))) {
+        Ok(json) => serde_json::from_str(&json).map_err(|e| AnalysisError::JsonError(e.to_string()))?,
+        Err(e) if e.kind() == ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(e.into()),
+    };
+    Ok(Some(CachedVariant {
+        path,
+        variant_hash: entry.variant_hash,
+        notes: entry.notes,
+        watermark_applied: entry.watermark_applied,
+    }))
+}
+
+/// Stores `mutated` under `key`. Each file is written to a unique temporary
+/// name and renamed into place, so concurrent writers of the same key never
+/// expose a partial file; the last rename wins with identical content.
+pub(crate) fn store(dir: &Path, key: &str, mutated: &MutatedPdfBytes) -> Result<()> {
+    fs::create_dir_all(dir)?;
+    let entry = CacheEntry {
+        variant_hash: mutated.variant_hash.clone(),
+        notes: mutated.notes.clone(),
+        watermark_applied: mutated.watermark_applied,
+    };
+    let json = serde_json::to_vec(&entry).map_err(|e| AnalysisError::JsonError(e.to_string()))?;
+    publish(dir, &format!("{}.json", key), &json)?;
+    publish(dir, &format!("{}.pdf", key), &mutated.bytes)
+}
+
+fn publish(dir: &Path, name: &str, bytes: &[u8]) -> Result<()> {
+    let temp = dir.join(format!(".{}.{}.tmp", name, Uuid::new_v4()));
+    fs::write(&temp, bytes)?;
+    fs::rename(&temp, dir.join(name)).inspect_err(|_| {
+        let _ = fs::remove_file(&temp);
+    })?;
+    Ok(())
+}
+
+/// Hardlinks the cached PDF to `output`, copying when linking fails (e.g.
+/// across file systems). An existing `output` is replaced.
+pub(crate) fn link_or_copy(cached: &Path, output: &Path) -> Result<()> {
+    remove_if_present(output)?;
+    if fs::hard_link(cached, output).is_err() {
+        fs::copy(cached, output)?;
+    }
+    Ok(())
+}
+
+/// Removes `path` so a following write creates a new file instead of
+/// writing through a hardlink into the cache.
+pub(crate) fn remove_if_present(path: &Path) -> Result<()> {
+    match fs::remove_file(path) {
+        Err(e) if e.kind() != ErrorKind::NotFound => Err(e.into()),
+        _ => Ok(()),
+    }
+}
@@ -0,0 +1,126 @@
+use super::scanner::TINY_FONT_THRESHOLD;
+use crate::pdf_utils::{self, cmap, DecodeBudget, DecodeLimits, TextRun};
+use crate::{AnalysisError, PdfLoadError, Result};
+use lopdf::content::Content;
+use lopdf::Document;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Fill colors at least this light (relative luminance, 0.0 black to 1.0
+/// white) are treated as invisible on a white page.
+pub const FAINT_COLOR_LUMINANCE: f64 = 0.85;
+
+/// Why a text run is considered invisible to a human reader.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum HiddenReason {
+    /// Drawn with a rendering mode that paints nothing (`3 Tr` or `7 Tr`).
+    InvisibleRenderMode,
+    /// Positioned outside the page MediaBox.
+    OffPage,
+    /// Smaller than [`TINY_FONT_THRESHOLD`].
+    TinyFont,
+    /// Filled with a color at or above [`FAINT_COLOR_LUMINANCE`].
+    FaintColor,
+}
+
+/// A text run a parser reads but a reader would not see.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct HiddenRun {
+    /// 1-based page number.
+    pub page: u32,
+    pub text: String,
+    pub reason: HiddenReason,
+}
+
+/// What a human sees on the pages versus what a parser extracts.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct DivergenceReport {
+    /// Text of the runs judged visible, in reading order.
+    pub visible_text: String,
+    /// Output of [`pdf_utils::extract_text_from_pdf`].
+    pub extracted_text: String,
+    /// Words of `extracted_text`, in extraction order, left over once every
+    /// visible word is accounted for: the text only machines read.
+    pub extra_text: String,
+    /// The runs judged invisible, with the reason for each.
+    pub hidden_runs: Vec<HiddenRun>,
+}
+
+impl DivergenceReport {
+    /// Whether the extractable text holds anything a reader cannot see.
+    pub fn diverges(&self) -> bool {
+        !self.extra_text.is_empty()
+    }
+}
+
+/// Approximates the text a reader sees (runs at a readable size, on the
+/// page, in a painting render mode and a non-faint color, assuming a white
+/// background) and compares it with [`pdf_utils::extract_text_from_pdf`].
+///
+/// Only page content streams are considered; text in form XObjects and
+/// annotations is ignored on both sides.
+pub fn visible_vs_extracted(path: &Path) -> Result<DivergenceReport> {
+    let doc = Document::load(path).map_err(PdfLoadError::from)?;
+    let mut budget = DecodeBudget::new(DecodeLimits::default());
+    let mut font_cache = cmap::FontMapCache::default();
+    let mut visible_lines = Vec::new();
+    let mut hidden_runs = Vec::new();
+
+    for (page_number, page_id) in doc.get_pages() {
+        let media_box = pdf_utils::page_media_box(&doc, page_id);
+        let content = budget.page_content(&doc, page_id)?;
+        let content = Content::decode(&content).map_err(|e| AnalysisError::PdfError(e.to_string()))?;
+        let font_maps = cmap::page_font_maps(&doc, page_id, &mut font_cache);
+
+        let mut visible = Vec::new();
+        for run in pdf_utils::collect_text_runs_with_fonts(&content.operations, &font_maps) {
+            if run.text.trim().is_empty() {
+                continue;
+            }
+            match hidden_reason(&run, &media_box) {
+                Some(reason) => hidden_runs.push(HiddenRun { page: page_number, text: run.text, reason }),
+                None => visible.push(run),
+            }
+        }
+        visible_lines.extend(pdf_utils::group_lines(visible));
+    }
+
+    let visible_text = visible_lines.join("\n");
+    let extracted_text = pdf_utils::extract_text_from_pdf(path)?;
+    let extra_text = extra_words(&visible_text, &extracted_text).join(" ");
+    Ok(DivergenceReport { visible_text, extracted_text, extra_text, hidden_runs })
+}
+
+fn hidden_reason(run: &TextRun, media_box: &[f64; 4]) -> Option<HiddenReason> {
+    let [r, g, b] = run.fill_color;
+    if run.render_mode == 3 || run.render_mode == 7 {
+        Some(HiddenReason::InvisibleRenderMode)
+    } else if run.x < media_box[0] || run.x > media_box[2] || run.y < media_box[1] || run.y > media_box[3] {
+        Some(HiddenReason::OffPage)
+    } else if run.font_size < TINY_FONT_THRESHOLD {
+        Some(HiddenReason::TinyFont)
+    } else if 0.2126 * r + 0.7152 * g + 0.0722 * b >= FAINT_COLOR_LUMINANCE {
+        Some(HiddenReason::FaintColor)
+    } else {
+        None
+    }
+}
+
+/// Words of `extracted` not matched one-for-one by a word of `visible`.
+fn extra_words<'a>(visible: &str, extracted: &'a str) -> Vec<&'a str> {
+    let mut available: HashMap<&str, usize> = HashMap::new();
+    for word in visible.split_whitespace() {
+        *available.entry(word).or_default() += 1;
+    }
+    extracted
+        .split_whitespace()
+        .filter(|word| match available.get_mut(word) {
+            Some(count) if *count > 0 => {
+                *count -= 1;
+                false
+            }
+            _ => true,
+        })
+        .collect()
+}
@@ -0,0 +1,95 @@
+/// Values substituted into an output filename template.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FilenameParts<'a> {
+    /// `{scenario}`: the scenario ID.
+    pub scenario: &'a str,
+    /// `{variant_id}`: the variant ID.
+    pub variant_id: &'a str,
+    /// `{profile}` (or `{profile_id}`): profile IDs of the variant, without
+    /// the `pdf.` prefix.
+    pub profiles: &'a [String],
+    /// `{template}`: the template ID.
+    pub template: &'a str,
+    /// `{index}`: 1-based position of the variant in the run.
+    pub index: usize,
+    /// `{hash8}`: the variant hash, truncated to 8 characters.
+    pub hash: Option<&'a str>,
+    /// `{date}`: typically [`utc_date_today`].
+    pub date: &'a str,
+}
+
+/// Expands `{scenario}`, `{variant_id}`, `{profile}`, `{profile_id}`,
+/// `{template}`, `{index}`, `{hash8}` and `{date}` in `template`, e.g.
+/// `{scenario}__{profile}__{template}__{hash8}`.
+///
+/// Substituted values are sanitized so they cannot introduce path separators
+/// or characters invalid in filenames. `.pdf` is appended when missing.
+pub fn render_filename(template: &str, parts: &FilenameParts) -> String {
+    let profile = parts
+        .profiles
+        .iter()
+        .map(|id| id.strip_prefix("pdf.").unwrap_or(id))
+        .collect::<Vec<_>>()
+        .join("+");
+    let hash8: String = parts.hash.unwrap_or("nohash").chars().take(8).collect();
+
+    let mut name = template.to_string();
+    for (placeholder, value) in [
+        ("{scenario}", parts.scenario.to_string()),
+        ("{variant_id}", parts.variant_id.to_string()),
+        ("{profile_id}", profile.clone()),
+        ("{profile}", profile),
+        ("{template}", parts.template.to_string()),
+        ("{index}", parts.index.to_string()),
+        ("{hash8}", hash8),
+        ("{date}", parts.date.to_string()),
+    ] {
+        name = name.replace(placeholder, &sanitize_component(&value));
+    }
+
+    // The template itself may not contain directories either.
+    let mut name = sanitize_component(&name);
+    if !name.to_lowercase().ends_with(".pdf") {
+        name.push_str(".pdf");
+    }
+    name
+}
+
+/// Replaces path separators, reserved and control characters with `_` and
+/// strips leading dots so the result stays a plain file name.
+fn sanitize_component(value: &str) -> String {
+    let cleaned: String = value
+        .chars()
+        .map(|c| match c {
+            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '_',
+            c if c.is_control() => '_',
+            c => c,
+        })
+        .collect();
+    cleaned.trim_start_matches('.').trim().to_string()
+}
+
+/// Today's date in UTC as `YYYY-MM-DD`.
+pub fn utc_date_today() -> String {
+    let secs = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let (year, month, day) = civil_from_days((secs / 86_400) as i64);
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+/// Converts days since 1970-01-01 to a proleptic Gregorian date
+/// (Howard Hinnant's `civil_from_days`).
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = (if mp < 10 { mp + 3 } else { mp - 9 }) as u32;
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
@@ -0,0 +1,172 @@
+use super::scanner::{self, DetectionFinding, FindingCategory, STANDARD_INFO_KEYS};
+use crate::pdf_utils::{self, resolve, DecodeBudget, DecodeLimits};
+use crate::{AnalysisError, PdfLoadError, Result};
+use lopdf::content::Content;
+use lopdf::{dictionary, Document, Object, ObjectId};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+/// Audit trail of everything `sanitize` removed from a document.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct SanitizeReport {
+    /// Features that were removed, described like scanner findings.
+    pub removed: Vec<DetectionFinding>,
+}
+
+impl SanitizeReport {
+    /// Returns true if nothing had to be removed.
+    pub fn is_clean(&self) -> bool {
+        self.removed.is_empty()
+    }
+}
+
+/// Rewrites a PDF with known injections neutralized and saves it to `output`.
+///
+/// Removes OpenAction JavaScript, `/Launch` and `/GoToR` actions, full-page URI links, text that is tiny,
+/// invisible or outside the MediaBox, and non-standard Info keys.
+pub fn sanitize(input: &Path, output: &Path) -> Result<SanitizeReport> {
+    let mut doc = Document::load(input).map_err(PdfLoadError::from)?;
+    let report = sanitize_document(&mut doc)?;
+
+    if let Some(parent) = output.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let mut file = fs::File::create(output)?;
+    doc.save_to(&mut file)
+        .map_err(|e| AnalysisError::PdfError(format!("Failed to save PDF: {}", e)))?;
+
+    Ok(report)
+}
+
+/// Sanitizes an already loaded document in place. See [`sanitize`].
+pub fn sanitize_document(doc: &mut Document) -> Result<SanitizeReport> {
+    let mut report = SanitizeReport::default();
+
+    let mut budget = DecodeBudget::new(DecodeLimits::default());
+    let page_ids: Vec<ObjectId> = doc.page_iter().collect();
+    for (index, page_id) in page_ids.into_iter().enumerate() {
+        let page_number = index as u32 + 1;
+        let media_box = pdf_utils::page_media_box(doc, page_id);
+
+        // Drop hidden text operations, keeping everything else in order.
+        let content = budget.page_content(doc, page_id)?;
+        let mut content = Content::decode(&content).map_err(|e| AnalysisError::PdfError(e.to_string()))?;
+        let mut dropped = Vec::new();
+        for run in pdf_utils::collect_text_runs(&content.operations) {
+            if let Some(finding) = scanner::classify_text_run(&run, &media_box, page_number) {
+                dropped.push(run.op_index);
+                report.removed.push(finding);
+            }
+        }
+        if !dropped.is_empty() {
+            content.operations = content
+                .operations
+                .into_iter()
+                .enumerate()
+                .filter(|(index, _)| !dropped.contains(index))
+                .map(|(_, op)| op)
+                .collect();
+            let encoded = content.encode().map_err(|e| AnalysisError::PdfError(e.to_string()))?;
+            let stream_id = doc.add_object(lopdf::Stream::new(dictionary! {}, encoded));
+            if let Ok(page) = doc.get_object_mut(page_id).and_then(Object::as_dict_mut) {
+                page.set("Contents", Object::Reference(stream_id));
+            }
+        }
+
+        // Remove full-page tracking links.
+        let annots = match doc.get_dictionary(page_id).and_then(|p| p.get(b"Annots")) {
+            Ok(annots) => resolve(doc, annots).as_array().cloned().unwrap_or_default(),
+            Err(_) => continue,
+        };
+        let mut kept = Vec::new();
+        for annot in annots {
+            let uri = resolve(doc, &annot)
+                .as_dict()
+                .ok()
+                .and_then(|dict| scanner::full_page_link_uri(doc, dict, &media_box));
+            match uri {
+                Some(uri) => report.removed.push(DetectionFinding::new(
+                    FindingCategory::FullPageLink,
+                    Some(page_number),
+                    format!("Removed full-page link to {}", uri),
+                )),
+                None => kept.push(annot),
+            }
+        }
+        if let Ok(page) = doc.get_object_mut(page_id).and_then(Object::as_dict_mut) {
+            if kept.is_empty() {
+                page.remove(b"Annots");
+            } else {
+                page.set("Annots", Object::Array(kept));
+            }
+        }
+    }
+
+    // Remove JavaScript and external OpenActions from both the catalog and the trailer.
+    let catalog_action = doc
+        .catalog()
+        .ok()
+        .and_then(|c| c.get(b"OpenAction").ok())
+        .and_then(|action| removable_action(doc, action));
+    if catalog_action.is_some()
+        && let Ok(catalog) = doc.catalog_mut()
+    {
+        catalog.remove(b"OpenAction");
+    }
+    let trailer_action = doc
+        .trailer
+        .get(b"OpenAction")
+        .ok()
+        .and_then(|action| removable_action(doc, action));
+    if trailer_action.is_some() {
+        doc.trailer.remove(b"OpenAction");
+    }
+    report.removed.extend(catalog_action.into_iter().chain(trailer_action));
+
+    // Clear non-standard Info keys.
+    let info_ref = doc.trailer.get(b"Info").and_then(Object::as_reference).ok();
+    let info = match info_ref {
+        Some(id) => doc.get_object_mut(id).and_then(Object::as_dict_mut).ok(),
+        None => doc.trailer.get_mut(b"Info").and_then(Object::as_dict_mut).ok(),
+    };
+    if let Some(info) = info {
+        let custom_keys: Vec<Vec<u8>> = info
+            .iter()
+            .map(|(key, _)| key.clone())
+            .filter(|key| !STANDARD_INFO_KEYS.contains(&String::from_utf8_lossy(key).as_ref()))
+            .collect();
+        for key in custom_keys {
+            info.remove(&key);
+            report.removed.push(DetectionFinding::new(
+                FindingCategory::SuspiciousMetadata,
+                None,
+                format!("Removed Info key `{}`", String::from_utf8_lossy(&key)),
+            ));
+        }
+    }
+
+    doc.prune_objects();
+    Ok(report)
+}
+
+/// Describes an OpenAction that `sanitize` strips, or None if it is harmless.
+fn removable_action(doc: &Document, action: &Object) -> Option<DetectionFinding> {
+    if let Some(js) = scanner::javascript_payload(doc, action) {
+        return Some(
+            DetectionFinding::new(
+                FindingCategory::JavaScriptAction,
+                None,
+                format!("Removed OpenAction JavaScript ({} bytes)", js.len()),
+            )
+            .with_evidence(&js),
+        );
+    }
+    scanner::external_action(doc, action).map(|(subtype, target)| {
+        DetectionFinding::new(
+            FindingCategory::ExternalAction,
+            None,
+            format!("Removed /{} OpenAction targeting {}", subtype, target),
+        )
+    })
+}
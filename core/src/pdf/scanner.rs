@@ -0,0 +1,557 @@
+use crate::pdf_utils::{self, resolve, as_number, DecodeBudget, DecodeLimits, TextRun};
+use crate::{AnalysisError, PdfLoadError, Result};
+use lopdf::content::Content;
+use lopdf::{Dictionary, Document, Object};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Font sizes below this many points are treated as unreadable to humans.
+pub const TINY_FONT_THRESHOLD: f64 = 3.0;
+
+/// FreeText annotations drawn below this `/CA` opacity are treated as hidden.
+pub const LOW_OPACITY_THRESHOLD: f64 = 0.1;
+
+/// Fraction of the page area a link must cover to count as "full page".
+const FULL_PAGE_LINK_COVERAGE: f64 = 0.9;
+
+/// Hosts of common URL shorteners, which hide the real link target.
+const URL_SHORTENERS: &[&str] = &[
+    "bit.ly", "tinyurl.com", "t.co", "goo.gl", "ow.ly", "is.gd", "buff.ly", "rebrand.ly", "cutt.ly", "shorturl.at",
+    "tiny.cc", "rb.gy",
+];
+
+/// Keys a well-formed document Info dictionary is expected to contain.
+pub(crate) const STANDARD_INFO_KEYS: &[&str] = &[
+    "Title", "Author", "Subject", "Keywords", "Creator", "Producer", "CreationDate", "ModDate", "Trapped",
+];
+
+/// Lowercased phrases typical of instructions aimed at an AI screener.
+/// Evidence containing one raises a finding's confidence.
+const KNOWN_INJECTION_PHRASES: &[&str] = &[
+    "ignore previous",
+    "ignore all previous",
+    "ignore prior",
+    "disregard previous",
+    "disregard all previous",
+    "system note",
+    "evaluation directive",
+    "automated reviewer",
+    "highest possible",
+    "return a high score",
+    "hire immediately",
+];
+
+/// Confidence added when a finding's evidence contains a known injection phrase.
+const KNOWN_PHRASE_BOOST: f32 = 0.3;
+
+/// Kind of suspicious feature found by the scanner.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum FindingCategory {
+    /// Text rendered below the readable font size threshold.
+    TinyFont,
+    /// Text positioned outside the page MediaBox.
+    OffPageText,
+    /// Text drawn with the invisible rendering mode (`3 Tr`).
+    InvisibleText,
+    /// A JavaScript action executed when the document is opened.
+    JavaScriptAction,
+    /// A `/Launch` or `/GoToR` action executed when the document is opened.
+    ExternalAction,
+    /// A URI link annotation covering (almost) the whole page.
+    FullPageLink,
+    /// A non-standard key in the document Info dictionary.
+    SuspiciousMetadata,
+    /// An AcroForm field that is hidden or has a zero-size widget.
+    HiddenFormField,
+    /// A FreeText annotation drawn (almost) fully transparent.
+    LowOpacityAnnotation,
+    /// A URI link whose target looks like an exfiltration or tracking endpoint.
+    SuspiciousLink {
+        /// The link target.
+        url: String,
+        /// Why the target was flagged.
+        reason: String,
+    },
+}
+
+impl FindingCategory {
+    /// Variant name without any payload, for tabular output.
+    pub fn label(&self) -> &'static str {
+        match self {
+            FindingCategory::TinyFont => "TinyFont",
+            FindingCategory::OffPageText => "OffPageText",
+            FindingCategory::InvisibleText => "InvisibleText",
+            FindingCategory::JavaScriptAction => "JavaScriptAction",
+            FindingCategory::ExternalAction => "ExternalAction",
+            FindingCategory::FullPageLink => "FullPageLink",
+            FindingCategory::SuspiciousMetadata => "SuspiciousMetadata",
+            FindingCategory::HiddenFormField => "HiddenFormField",
+            FindingCategory::LowOpacityAnnotation => "LowOpacityAnnotation",
+            FindingCategory::SuspiciousLink { .. } => "SuspiciousLink",
+        }
+    }
+
+    /// Confidence that a finding of this kind is an injection, before
+    /// looking at its content.
+    pub fn base_confidence(&self) -> f32 {
+        match self {
+            FindingCategory::InvisibleText => 0.7,
+            FindingCategory::JavaScriptAction | FindingCategory::ExternalAction => 0.7,
+            FindingCategory::OffPageText | FindingCategory::HiddenFormField => 0.6,
+            FindingCategory::LowOpacityAnnotation => 0.6,
+            FindingCategory::FullPageLink | FindingCategory::SuspiciousLink { .. } => 0.5,
+            FindingCategory::TinyFont => 0.4,
+            FindingCategory::SuspiciousMetadata => 0.3,
+        }
+    }
+}
+
+/// A single suspicious feature detected in a PDF.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct DetectionFinding {
+    /// What kind of feature was found.
+    pub category: FindingCategory,
+    /// Page number the finding belongs to (None for document-level findings).
+    pub page: Option<u32>,
+    /// Short human-readable description.
+    pub description: String,
+    /// How likely (0.0–1.0) the feature is a deliberate injection.
+    #[serde(default)]
+    pub confidence: f32,
+}
+
+impl DetectionFinding {
+    /// Creates a finding at its category's [`FindingCategory::base_confidence`].
+    pub fn new(category: FindingCategory, page: Option<u32>, description: impl Into<String>) -> Self {
+        Self {
+            confidence: category.base_confidence(),
+            category,
+            page,
+            description: description.into(),
+        }
+    }
+
+    /// Raises the confidence when `evidence` (the hidden text, script or
+    /// value behind the finding) contains a known injection phrase.
+    pub fn with_evidence(mut self, evidence: &str) -> Self {
+        let evidence = evidence.to_lowercase();
+        if KNOWN_INJECTION_PHRASES.iter().any(|phrase| evidence.contains(phrase)) {
+            self.confidence = (self.confidence + KNOWN_PHRASE_BOOST).min(1.0);
+        }
+        self
+    }
+}
+
+/// Scan findings together with a document-level risk score.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ScanSummary {
+    pub findings: Vec<DetectionFinding>,
+    /// See [`risk_score`].
+    pub risk_score: f32,
+}
+
+impl ScanSummary {
+    /// Scores `findings` with [`risk_score`].
+    pub fn from_findings(findings: Vec<DetectionFinding>) -> Self {
+        let risk_score = risk_score(&findings);
+        Self { findings, risk_score }
+    }
+}
+
+/// Combines finding confidences as independent signals (noisy-OR), so the
+/// score only grows with more findings and stays within 0.0–1.0. A clean
+/// document scores 0.0.
+///
+/// Findings of the same category on the same page are one signal and
+/// contribute only their highest confidence, so a page with many tiny text
+/// runs does not outscore a single invisible instruction.
+pub fn risk_score(findings: &[DetectionFinding]) -> f32 {
+    let mut signals: Vec<(Option<u32>, &FindingCategory, f32)> = Vec::new();
+    for finding in findings {
+        let confidence = finding.confidence.clamp(0.0, 1.0);
+        match signals
+            .iter_mut()
+            .find(|(page, category, _)| *page == finding.page && **category == finding.category)
+        {
+            Some((_, _, max)) => *max = max.max(confidence),
+            None => signals.push((finding.page, &finding.category, confidence)),
+        }
+    }
+    1.0 - signals.iter().map(|(_, _, confidence)| 1.0 - confidence).product::<f32>()
+}
+
+/// Loads a PDF and flags features commonly used to inject hidden instructions.
+pub fn scan_for_injections(path: &Path) -> Result<Vec<DetectionFinding>> {
+    let doc = Document::load(path).map_err(PdfLoadError::from)?;
+    scan_document(&doc)
+}
+
+/// Scans like [`scan_for_injections`] and aggregates the findings into a
+/// [`ScanSummary`].
+pub fn scan_summary(path: &Path) -> Result<ScanSummary> {
+    scan_for_injections(path).map(ScanSummary::from_findings)
+}
+
+/// Scan outcome for one file visited by [`scan_path`].
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct FileScan {
+    pub path: PathBuf,
+    pub findings: Vec<DetectionFinding>,
+    /// [`risk_score`] of `findings`.
+    #[serde(default)]
+    pub risk_score: f32,
+    /// Set when the file could not be loaded or parsed; `findings` is then empty.
+    pub error: Option<String>,
+}
+
+/// Scans a single PDF, or every `.pdf` below a directory (recursively, in
+/// path order, without following symlinked directories). A file that fails to load is reported through
+/// [`FileScan::error`] instead of aborting the whole scan.
+pub fn scan_path(path: &Path) -> Result<Vec<FileScan>> {
+    let files = if path.is_dir() {
+        let mut files = Vec::new();
+        collect_pdfs(path, &mut files)?;
+        files.sort();
+        files
+    } else {
+        vec![path.to_path_buf()]
+    };
+
+    Ok(files
+        .into_iter()
+        .map(|path| match scan_for_injections(&path) {
+            Ok(findings) => FileScan { path, risk_score: risk_score(&findings), findings, error: None },
+            Err(e) => FileScan { path, findings: Vec::new(), risk_score: 0.0, error: Some(e.to_string()) },
+        })
+        .collect())
+}
+
+fn collect_pdfs(dir: &Path, files: &mut Vec<PathBuf>) -> Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let path = entry.path();
+        // `file_type` does not follow symlinks, so a link back up the tree
+        // cannot send the walk into a cycle.
+        if entry.file_type()?.is_dir() {
+            collect_pdfs(&path, files)?;
+        } else if path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("pdf")) {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// Scans an already loaded document. See [`scan_for_injections`].
+pub fn scan_document(doc: &Document) -> Result<Vec<DetectionFinding>> {
+    let mut findings = Vec::new();
+    let mut budget = DecodeBudget::new(DecodeLimits::default());
+
+    for (index, page_id) in doc.page_iter().enumerate() {
+        let page_number = index as u32 + 1;
+        let media_box = pdf_utils::page_media_box(doc, page_id);
+        let content = budget.page_content(doc, page_id)?;
+        let content = Content::decode(&content).map_err(|e| AnalysisError::PdfError(e.to_string()))?;
+
+        findings.extend(
+            pdf_utils::collect_text_runs(&content.operations)
+                .iter()
+                .filter_map(|run| classify_text_run(run, &media_box, page_number)),
+        );
+
+        if let Ok(page) = doc.get_dictionary(page_id) {
+            for url in full_page_links(doc, page, &media_box) {
+                findings.push(DetectionFinding::new(
+                    FindingCategory::FullPageLink,
+                    Some(page_number),
+                    format!("Link annotation covering the page points to {}", url),
+                ));
+            }
+            for (opacity, contents) in low_opacity_freetext(doc, page) {
+                findings.push(
+                    DetectionFinding::new(
+                        FindingCategory::LowOpacityAnnotation,
+                        Some(page_number),
+                        format!("FreeText annotation at {:.2} opacity holds \"{}\"", opacity, excerpt(&contents)),
+                    )
+                    .with_evidence(&contents),
+                );
+            }
+            for url in link_uris(doc, page) {
+                if let Some(reason) = suspicious_link_reason(&url) {
+                    let description = format!("Suspicious link to {}: {}", url, reason);
+                    findings.push(DetectionFinding::new(
+                        FindingCategory::SuspiciousLink { url, reason },
+                        Some(page_number),
+                        description,
+                    ));
+                }
+            }
+        }
+    }
+
+    let catalog_action = doc.catalog().ok().and_then(|c| c.get(b"OpenAction").ok());
+    let trailer_action = doc.trailer.get(b"OpenAction").ok();
+    for action in catalog_action.into_iter().chain(trailer_action) {
+        if let Some(js) = javascript_payload(doc, action) {
+            findings.push(
+                DetectionFinding::new(
+                    FindingCategory::JavaScriptAction,
+                    None,
+                    format!("OpenAction runs JavaScript: \"{}\"", excerpt(&js)),
+                )
+                .with_evidence(&js),
+            );
+        }
+        if let Some((subtype, target)) = external_action(doc, action) {
+            findings.push(DetectionFinding::new(
+                FindingCategory::ExternalAction,
+                None,
+                format!("OpenAction runs /{} on \"{}\"", subtype, excerpt(&target)),
+            ));
+        }
+    }
+
+    for (name, value) in hidden_form_fields(doc) {
+        findings.push(
+            DetectionFinding::new(
+                FindingCategory::HiddenFormField,
+                None,
+                format!("Hidden form field `{}` holds \"{}\"", name, excerpt(&value)),
+            )
+            .with_evidence(&value),
+        );
+    }
+
+    if let Some(info) = info_dictionary(doc) {
+        for (key, value) in info.iter() {
+            let key = String::from_utf8_lossy(key);
+            if !STANDARD_INFO_KEYS.contains(&key.as_ref()) {
+                let value = resolve(doc, value).as_str().map(String::from_utf8_lossy).unwrap_or_default();
+                findings.push(
+                    DetectionFinding::new(FindingCategory::SuspiciousMetadata, None, format!("Non-standard Info key `{}`", key))
+                        .with_evidence(&value),
+                );
+            }
+        }
+    }
+
+    Ok(findings)
+}
+
+/// Annotation flags (`Hidden`, `NoView`) that keep a widget off screen.
+const HIDDEN_ANNOT_FLAGS: i64 = 2 | 32;
+
+/// Returns the name and value of every AcroForm field whose widget is hidden
+/// or has a zero-area `Rect`, descending into `Kids`.
+fn hidden_form_fields(doc: &Document) -> Vec<(String, String)> {
+    let Some(fields) = doc
+        .catalog()
+        .ok()
+        .and_then(|c| resolve(doc, c.get(b"AcroForm").ok()?).as_dict().ok())
+        .and_then(|form| resolve(doc, form.get(b"Fields").ok()?).as_array().ok())
+    else {
+        return Vec::new();
+    };
+
+    let mut hidden = Vec::new();
+    let mut pending: Vec<&Object> = fields.iter().rev().collect();
+    while let Some(field) = pending.pop() {
+        let Ok(field) = resolve(doc, field).as_dict() else { continue };
+        if let Ok(kids) = field.get(b"Kids").map(|k| resolve(doc, k)).and_then(Object::as_array) {
+            pending.extend(kids.iter());
+        }
+        let flags = field.get(b"F").ok().and_then(as_number).unwrap_or(0.0) as i64;
+        let rect: Vec<f64> = field
+            .get(b"Rect")
+            .map(|r| resolve(doc, r))
+            .and_then(Object::as_array)
+            .map(|r| r.iter().filter_map(as_number).collect())
+            .unwrap_or_default();
+        let zero_size = rect.len() == 4 && ((rect[2] - rect[0]) * (rect[3] - rect[1])).abs() < f64::EPSILON;
+        if flags & HIDDEN_ANNOT_FLAGS == 0 && !zero_size {
+            continue;
+        }
+        let text = |key: &[u8]| {
+            field
+                .get(key)
+                .map(|v| resolve(doc, v))
+                .and_then(Object::as_str)
+                .map(|s| String::from_utf8_lossy(s).into_owned())
+                .unwrap_or_default()
+        };
+        hidden.push((text(b"T"), text(b"V")));
+    }
+    hidden
+}
+
+/// Returns the URIs of link annotations on the page that cover most of it.
+fn full_page_links(doc: &Document, page: &Dictionary, media_box: &[f64; 4]) -> Vec<String> {
+    let Ok(annots) = page.get(b"Annots").map(|a| resolve(doc, a)).and_then(Object::as_array) else {
+        return Vec::new();
+    };
+
+    annots
+        .iter()
+        .filter_map(|annot| resolve(doc, annot).as_dict().ok())
+        .filter_map(|annot| full_page_link_uri(doc, annot, media_box))
+        .collect()
+}
+
+/// Returns the opacity and contents of FreeText annotations on the page that
+/// are drawn below [`LOW_OPACITY_THRESHOLD`].
+fn low_opacity_freetext(doc: &Document, page: &Dictionary) -> Vec<(f64, String)> {
+    let Ok(annots) = page.get(b"Annots").map(|a| resolve(doc, a)).and_then(Object::as_array) else {
+        return Vec::new();
+    };
+
+    annots
+        .iter()
+        .filter_map(|annot| resolve(doc, annot).as_dict().ok())
+        .filter(|annot| annot.get(b"Subtype").and_then(Object::as_name).ok() == Some(b"FreeText".as_slice()))
+        .filter_map(|annot| {
+            let opacity = annot.get(b"CA").ok().and_then(as_number).unwrap_or(1.0);
+            if opacity >= LOW_OPACITY_THRESHOLD {
+                return None;
+            }
+            let contents = annot.get(b"Contents").and_then(Object::as_str).unwrap_or_default();
+            Some((opacity, String::from_utf8_lossy(contents).into_owned()))
+        })
+        .collect()
+}
+
+/// Returns the URI targets of every link annotation on the page.
+fn link_uris(doc: &Document, page: &Dictionary) -> Vec<String> {
+    let Ok(annots) = page.get(b"Annots").map(|a| resolve(doc, a)).and_then(Object::as_array) else {
+        return Vec::new();
+    };
+
+    annots
+        .iter()
+        .filter_map(|annot| resolve(doc, annot).as_dict().ok())
+        .filter(|annot| annot.get(b"Subtype").and_then(Object::as_name).ok() == Some(b"Link".as_slice()))
+        .filter_map(|annot| {
+            let action = resolve(doc, annot.get(b"A").ok()?).as_dict().ok()?;
+            let uri = action.get(b"URI").ok()?.as_str().ok()?;
+            Some(String::from_utf8_lossy(uri).into_owned())
+        })
+        .collect()
+}
+
+/// Explains why a link target is suspicious: a non-https scheme, a bare IP
+/// address or a known URL shortener. Returns None for ordinary links,
+/// including the `mailto:` and `tel:` links of a resume's contact line.
+pub(crate) fn suspicious_link_reason(url: &str) -> Option<String> {
+    let Ok(parsed) = reqwest::Url::parse(url.trim()) else {
+        return Some("malformed URL".to_string());
+    };
+    if matches!(parsed.scheme(), "mailto" | "tel") {
+        return None;
+    }
+    if parsed.scheme() != "https" {
+        return Some(format!("non-https scheme `{}`", parsed.scheme()));
+    }
+    let host = parsed.host_str()?.trim_start_matches('[').trim_end_matches(']').to_lowercase();
+    if host.parse::<std::net::IpAddr>().is_ok() {
+        return Some("target is a bare IP address".to_string());
+    }
+    let is_shortener = URL_SHORTENERS
+        .iter()
+        .any(|shortener| host == *shortener || host.ends_with(&format!(".{}", shortener)));
+    if is_shortener {
+        return Some(format!("URL shortener `{}` hides the target", host));
+    }
+    None
+}
+
+/// Returns the target URI if the annotation is a link covering most of the page.
+pub(crate) fn full_page_link_uri(doc: &Document, annot: &Dictionary, media_box: &[f64; 4]) -> Option<String> {
+    if annot.get(b"Subtype").and_then(Object::as_name).ok()? != b"Link" {
+        return None;
+    }
+    let rect: Vec<f64> = resolve(doc, annot.get(b"Rect").ok()?)
+        .as_array()
+        .ok()?
+        .iter()
+        .filter_map(as_number)
+        .collect();
+    if rect.len() != 4 {
+        return None;
+    }
+    let page_area = (media_box[2] - media_box[0]) * (media_box[3] - media_box[1]);
+    let width = (rect[2] - rect[0]).abs().min(media_box[2] - media_box[0]);
+    let height = (rect[3] - rect[1]).abs().min(media_box[3] - media_box[1]);
+    if page_area <= 0.0 || width * height / page_area < FULL_PAGE_LINK_COVERAGE {
+        return None;
+    }
+    let action = resolve(doc, annot.get(b"A").ok()?).as_dict().ok()?;
+    let uri = action.get(b"URI").ok()?.as_str().ok()?;
+    Some(String::from_utf8_lossy(uri).into_owned())
+}
+
+/// Classifies a text run, returning the finding it triggers (if any).
+pub(crate) fn classify_text_run(run: &TextRun, media_box: &[f64; 4], page_number: u32) -> Option<DetectionFinding> {
+    if run.text.trim().is_empty() {
+        return None;
+    }
+    let excerpt = excerpt(&run.text);
+    let (category, description) = if run.render_mode == 3 {
+        (FindingCategory::InvisibleText, format!("Invisible text (3 Tr): \"{}\"", excerpt))
+    } else if run.x < media_box[0] || run.x > media_box[2] || run.y < media_box[1] || run.y > media_box[3] {
+        (
+            FindingCategory::OffPageText,
+            format!("Text outside MediaBox at ({:.0}, {:.0}): \"{}\"", run.x, run.y, excerpt),
+        )
+    } else if run.font_size < TINY_FONT_THRESHOLD {
+        (FindingCategory::TinyFont, format!("Text at {:.1}pt: \"{}\"", run.font_size, excerpt))
+    } else {
+        return None;
+    };
+    Some(DetectionFinding::new(category, Some(page_number), description).with_evidence(&run.text))
+}
+
+/// Extracts the script of a JavaScript action, if the object is one.
+pub(crate) fn javascript_payload(doc: &Document, action: &Object) -> Option<String> {
+    let action = resolve(doc, action).as_dict().ok()?;
+    if action.get(b"S").and_then(Object::as_name).ok()? != b"JavaScript" {
+        return None;
+    }
+    match action.get(b"JS").map(|js| resolve(doc, js)) {
+        Ok(Object::String(bytes, _)) => Some(String::from_utf8_lossy(bytes).into_owned()),
+        Ok(Object::Stream(stream)) => Some(String::from_utf8_lossy(&stream.content).into_owned()),
+        _ => Some(String::new()),
+    }
+}
+
+/// Returns the subtype and target file of a `/Launch` or `/GoToR` action,
+/// if the object is one.
+pub(crate) fn external_action(doc: &Document, action: &Object) -> Option<(String, String)> {
+    let action = resolve(doc, action).as_dict().ok()?;
+    let subtype = action.get(b"S").and_then(Object::as_name).ok()?;
+    if subtype != b"Launch" && subtype != b"GoToR" {
+        return None;
+    }
+    // `/F` is either a plain file name or a file specification dictionary.
+    let target = match action.get(b"F").map(|f| resolve(doc, f)) {
+        Ok(Object::String(bytes, _)) => String::from_utf8_lossy(bytes).into_owned(),
+        Ok(Object::Dictionary(spec)) => match spec.get(b"UF").or_else(|_| spec.get(b"F")) {
+            Ok(Object::String(bytes, _)) => String::from_utf8_lossy(bytes).into_owned(),
+            _ => String::new(),
+        },
+        _ => String::new(),
+    };
+    Some((String::from_utf8_lossy(subtype).into_owned(), target))
+}
+
+fn info_dictionary(doc: &Document) -> Option<&Dictionary> {
+    resolve(doc, doc.trailer.get(b"Info").ok()?).as_dict().ok()
+}
+
+fn excerpt(text: &str) -> String {
+    let trimmed = text.trim();
+    if trimmed.chars().count() > 60 {
+        format!("{}...", trimmed.chars().take(60).collect::<String>())
+    } else {
+        trimmed.to_string()
+    }
+}
@@ -1,8 +1,31 @@
-use lopdf::{Document, Object, dictionary, content::{Content, Operation}};
+use lopdf::{Document, IncrementalDocument, Object, ObjectId, ObjectStream, Reader, dictionary, content::{Content, Operation}};
+use lopdf::encryption::{self, crypt_filters::{Aes256CryptFilter, CryptFilter}, EncryptionState, EncryptionVersion, Permissions};
+use lopdf::xref::XrefEntry;
+use rand::Rng;
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::sync::Arc;
 use crate::Result;
-use crate::AnalysisError;
+use crate::attacks::ActionKind;
+use crate::{AnalysisError, PdfLoadError};
 
-/// Adds text to a specific page at given coordinates.
+pub mod cmap;
+
+pub use cmap::ToUnicodeMap;
+
+/// Returns the object id of the `page_number`-th page (1-based) in document order.
+///
+/// Walks the page tree instead of trusting the keys of `get_pages()`, so
+/// "page 1" is always the visually first page.
+pub fn nth_page_id(doc: &Document, page_number: u32) -> Result<ObjectId> {
+    page_number
+        .checked_sub(1)
+        .and_then(|index| doc.page_iter().nth(index as usize))
+        .ok_or_else(|| AnalysisError::PdfError(format!("Page {} not found", page_number)))
+}
+
+/// Adds text to a specific page at given coordinates. With `max_width`, the
+/// text wraps onto further lines flowing downwards from `y`.
+#[allow(clippy::too_many_arguments)]
 pub fn add_text_to_page(
     doc: &mut Document,
     page_number: u32,
@@ -10,10 +33,82 @@ pub fn add_text_to_page(
     x: f64,
     y: f64,
     font_size: f64,
-    color_gray: f64,
+    color: impl Into<FillColor>,
+    max_width: Option<f64>,
+) -> Result<()> {
+    let lines = text_lines(text, x, y, font_size, max_width);
+    append_text_object(doc, page_number, font_size, color.into(), lines)
+}
+
+/// Adds text like [`add_text_to_page`], but shows every word with its own
+/// `Tj` operation, stepping along the line with `Td`. Extractors still read
+/// the words back in order, while matchers scanning single operations never
+/// see the whole phrase.
+#[allow(clippy::too_many_arguments)]
+pub fn add_fragmented_text_to_page(
+    doc: &mut Document,
+    page_number: u32,
+    text: &str,
+    x: f64,
+    y: f64,
+    font_size: f64,
+    color: impl Into<FillColor>,
+    max_width: Option<f64>,
 ) -> Result<()> {
-    let pages = doc.get_pages();
-    let page_id = *pages.get(&page_number).ok_or_else(|| AnalysisError::PdfError(format!("Page {} not found", page_number)))?;
+    let lines = fragmented_text_lines(text, x, y, font_size, max_width);
+    append_text_object(doc, page_number, font_size, color.into(), lines)
+}
+
+/// Non-stroking color for drawn text.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FillColor {
+    /// Gray level (`g`), 0.0 black to 1.0 white.
+    Gray(f64),
+    /// RGB (`rg`), each component 0.0 to 1.0.
+    Rgb([f64; 3]),
+}
+
+impl FillColor {
+    fn operation(&self) -> Operation {
+        match *self {
+            FillColor::Gray(gray) => Operation::new("g", vec![gray.into()]),
+            FillColor::Rgb([r, g, b]) => Operation::new("rg", vec![r.into(), g.into(), b.into()]),
+        }
+    }
+}
+
+impl From<f64> for FillColor {
+    fn from(gray: f64) -> Self {
+        FillColor::Gray(gray)
+    }
+}
+
+/// Smallest distance [`shade_near`] moves away from the background.
+const MIN_SHADE_DELTA: f64 = 1.0 / 255.0;
+
+/// A color at a Euclidean RGB distance of 90% of `delta` from `background`,
+/// moving every component toward mid-gray so it stays within 0.0–1.0. The
+/// result is never identical to the background: `delta` is raised to at
+/// least one 8-bit step and capped at the RGB cube's diagonal.
+pub fn shade_near(background: [f64; 3], delta: f64) -> [f64; 3] {
+    let delta = if delta.is_nan() { MIN_SHADE_DELTA } else { delta.clamp(MIN_SHADE_DELTA, 3f64.sqrt()) };
+    let step = (0.9 * delta / 3f64.sqrt()).min(0.5);
+    background.map(|component| {
+        let component = component.clamp(0.0, 1.0);
+        if component > 0.5 { component - step } else { component + step }
+    })
+}
+
+/// Appends a `BT`/`ET` text object drawing `lines` in Helvetica as a new
+/// content stream of the page.
+fn append_text_object(
+    doc: &mut Document,
+    page_number: u32,
+    font_size: f64,
+    color: FillColor,
+    lines: Vec<Operation>,
+) -> Result<()> {
+    let page_id = nth_page_id(doc, page_number)?;
 
     // Ensure font exists
     let font_id = doc.add_object(dictionary! {
@@ -50,9 +145,8 @@ pub fn add_text_to_page(
     let mut operations = Vec::new();
     operations.push(Operation::new("BT", vec![]));
     operations.push(Operation::new("Tf", vec!["F1".into(), font_size.into()]));
-    operations.push(Operation::new("g", vec![color_gray.into()]));
-    operations.push(Operation::new("Td", vec![x.into(), y.into()]));
-    operations.push(Operation::new("Tj", vec![Object::string_literal(text)]));
+    operations.push(color.operation());
+    operations.extend(lines);
     operations.push(Operation::new("ET", vec![]));
 
     let content = Content { operations };
@@ -96,6 +190,8 @@ pub fn add_text_to_page(
 }
 
 /// Adds text to a specific page at given coordinates, ensuring it is rendered *before* existing content (underlay).
+/// Wraps like [`add_text_to_page`] when `max_width` is set.
+#[allow(clippy::too_many_arguments)]
 pub fn prepend_text_to_page(
     doc: &mut Document,
     page_number: u32,
@@ -104,9 +200,9 @@ pub fn prepend_text_to_page(
     y: f64,
     font_size: f64,
     color_gray: f64,
+    max_width: Option<f64>,
 ) -> Result<()> {
-    let pages = doc.get_pages();
-    let page_id = *pages.get(&page_number).ok_or_else(|| AnalysisError::PdfError(format!("Page {} not found", page_number)))?;
+    let page_id = nth_page_id(doc, page_number)?;
 
     // Ensure font exists (reuse logic or refactor if needed, for now duplicating for safety)
     let font_id = doc.add_object(dictionary! {
@@ -142,8 +238,7 @@ pub fn prepend_text_to_page(
     operations.push(Operation::new("BT", vec![]));
     operations.push(Operation::new("Tf", vec!["F1".into(), font_size.into()]));
     operations.push(Operation::new("g", vec![color_gray.into()]));
-    operations.push(Operation::new("Td", vec![x.into(), y.into()]));
-    operations.push(Operation::new("Tj", vec![Object::string_literal(text)]));
+    operations.extend(text_lines(text, x, y, font_size, max_width));
     operations.push(Operation::new("ET", vec![]));
 
     let content = Content { operations };
@@ -185,6 +280,296 @@ pub fn prepend_text_to_page(
     Ok(())
 }
 
+/// Adds text wrapped in an Optional Content Group whose default state is off,
+/// so viewers hide it while content-stream text extractors still read it.
+/// Every page in `pages` draws the text inside the same group, one `Tj` per
+/// word when `fragment` is set.
+#[allow(clippy::too_many_arguments)]
+pub fn add_hidden_ocg_text(
+    doc: &mut Document,
+    pages: &[u32],
+    text: &str,
+    x: f64,
+    y: f64,
+    font_size: f64,
+    max_width: Option<f64>,
+    fragment: bool,
+) -> Result<()> {
+    let page_ids = pages.iter().map(|&page_number| nth_page_id(doc, page_number)).collect::<Result<Vec<_>>>()?;
+
+    let ocg_id = doc.add_object(dictionary! {
+        "Type" => "OCG",
+        "Name" => Object::string_literal("Hidden Layer"),
+        "Usage" => dictionary! {
+            "View" => dictionary! { "ViewState" => "OFF" },
+            "Print" => dictionary! { "PrintState" => "OFF" },
+        },
+    });
+    let font_id = doc.add_object(dictionary! {
+        "Type" => "Font",
+        "Subtype" => "Type1",
+        "BaseFont" => "Helvetica",
+    });
+
+    // Register the group in the catalog and switch it off by default
+    let catalog_id = catalog_id(doc)?;
+    let oc_properties_id = indirect_dict_entry(doc, catalog_id, b"OCProperties")?;
+    push_to_array(doc, oc_properties_id, b"OCGs", Object::Reference(ocg_id))?;
+    let config_id = indirect_dict_entry(doc, oc_properties_id, b"D")?;
+    let config = doc
+        .get_dictionary_mut(config_id)
+        .map_err(|e| AnalysisError::PdfError(e.to_string()))?;
+    if !config.has(b"BaseState") {
+        config.set("BaseState", "ON");
+    }
+    push_to_array(doc, config_id, b"Order", Object::Reference(ocg_id))?;
+    push_to_array(doc, config_id, b"OFF", Object::Reference(ocg_id))?;
+
+    let mut operations = vec![
+        Operation::new("BDC", vec!["OC".into(), "OCHidden".into()]),
+        Operation::new("BT", vec![]),
+        Operation::new("Tf", vec!["F1".into(), font_size.into()]),
+    ];
+    if fragment {
+        operations.extend(fragmented_text_lines(text, x, y, font_size, max_width));
+    } else {
+        operations.extend(text_lines(text, x, y, font_size, max_width));
+    }
+    operations.push(Operation::new("ET", vec![]));
+    operations.push(Operation::new("EMC", vec![]));
+    let content = Content { operations };
+    let encoded = content.encode().map_err(|e| AnalysisError::PdfError(e.to_string()))?;
+    let content_stream = doc.add_object(lopdf::Stream::new(dictionary! {}, encoded));
+
+    for page_id in page_ids {
+        // Expose the font and the group through the page resources
+        let resources_id = indirect_dict_entry(doc, page_id, b"Resources")?;
+        let fonts_id = indirect_dict_entry(doc, resources_id, b"Font")?;
+        doc.get_dictionary_mut(fonts_id)
+            .map_err(|e| AnalysisError::PdfError(e.to_string()))?
+            .set("F1", Object::Reference(font_id));
+        let properties_id = indirect_dict_entry(doc, resources_id, b"Properties")?;
+        doc.get_dictionary_mut(properties_id)
+            .map_err(|e| AnalysisError::PdfError(e.to_string()))?
+            .set("OCHidden", Object::Reference(ocg_id));
+
+        let page = doc
+            .get_dictionary_mut(page_id)
+            .map_err(|e| AnalysisError::PdfError(e.to_string()))?;
+        match page.get_mut(b"Contents") {
+            Ok(Object::Reference(id)) => {
+                let existing = *id;
+                page.set("Contents", vec![Object::Reference(existing), Object::Reference(content_stream)]);
+            }
+            Ok(Object::Array(items)) => items.push(Object::Reference(content_stream)),
+            _ => page.set("Contents", Object::Reference(content_stream)),
+        }
+    }
+
+    Ok(())
+}
+
+/// Stamps `text` diagonally across the centre of a page in semi-transparent
+/// gray, drawn over the existing content.
+pub fn add_watermark(doc: &mut Document, page_number: u32, text: &str) -> Result<()> {
+    const FONT_SIZE: f64 = 48.0;
+    const OPACITY: f64 = 0.25;
+
+    let page_id = nth_page_id(doc, page_number)?;
+    let [llx, lly, urx, ury] = page_media_box(doc, page_id);
+
+    let font_id = doc.add_object(dictionary! {
+        "Type" => "Font",
+        "Subtype" => "Type1",
+        "BaseFont" => "Helvetica-Bold",
+    });
+    let gs_id = doc.add_object(dictionary! {
+        "Type" => "ExtGState",
+        "ca" => OPACITY,
+        "CA" => OPACITY,
+    });
+
+    let resources_id = indirect_dict_entry(doc, page_id, b"Resources")?;
+    let fonts_id = indirect_dict_entry(doc, resources_id, b"Font")?;
+    doc.get_dictionary_mut(fonts_id)
+        .map_err(|e| AnalysisError::PdfError(e.to_string()))?
+        .set("FWatermark", Object::Reference(font_id));
+    let states_id = indirect_dict_entry(doc, resources_id, b"ExtGState")?;
+    doc.get_dictionary_mut(states_id)
+        .map_err(|e| AnalysisError::PdfError(e.to_string()))?
+        .set("GSWatermark", Object::Reference(gs_id));
+
+    // Rotate along the page diagonal and shift back by half the (estimated)
+    // text width so the stamp is centred.
+    let angle = (ury - lly).atan2(urx - llx);
+    let (sin, cos) = angle.sin_cos();
+    let half_width = text.chars().count() as f64 * FONT_SIZE * 0.6 / 2.0;
+    let x = (llx + urx) / 2.0 - half_width * cos;
+    let y = (lly + ury) / 2.0 - half_width * sin;
+
+    let operations = vec![
+        Operation::new("q", vec![]),
+        Operation::new("gs", vec!["GSWatermark".into()]),
+        Operation::new("BT", vec![]),
+        Operation::new("Tf", vec!["FWatermark".into(), FONT_SIZE.into()]),
+        Operation::new("g", vec![0.5.into()]),
+        Operation::new("Tm", vec![cos.into(), sin.into(), (-sin).into(), cos.into(), x.into(), y.into()]),
+        Operation::new("Tj", vec![Object::string_literal(text)]),
+        Operation::new("ET", vec![]),
+        Operation::new("Q", vec![]),
+    ];
+    let content = Content { operations };
+    let encoded = content.encode().map_err(|e| AnalysisError::PdfError(e.to_string()))?;
+    let content_stream = doc.add_object(lopdf::Stream::new(dictionary! {}, encoded));
+
+    let page = doc
+        .get_dictionary_mut(page_id)
+        .map_err(|e| AnalysisError::PdfError(e.to_string()))?;
+    match page.get_mut(b"Contents") {
+        Ok(Object::Reference(id)) => {
+            let existing = *id;
+            page.set("Contents", vec![Object::Reference(existing), Object::Reference(content_stream)]);
+        }
+        Ok(Object::Array(items)) => items.push(Object::Reference(content_stream)),
+        _ => page.set("Contents", Object::Reference(content_stream)),
+    }
+
+    Ok(())
+}
+
+/// Appends `value` to the array under `key` in the dictionary `parent_id`,
+/// creating the array if needed. An array stored as its own object is
+/// updated through the reference.
+fn push_to_array(doc: &mut Document, parent_id: ObjectId, key: &[u8], value: Object) -> Result<()> {
+    let parent = doc
+        .get_dictionary_mut(parent_id)
+        .map_err(|e| AnalysisError::PdfError(e.to_string()))?;
+    let array_id = match parent.get_mut(key) {
+        Ok(Object::Array(items)) => {
+            items.push(value);
+            return Ok(());
+        }
+        Ok(Object::Reference(id)) => *id,
+        Ok(_) => {
+            return Err(AnalysisError::PdfError(format!("/{} is not an array", String::from_utf8_lossy(key))));
+        }
+        Err(_) => {
+            parent.set(key.to_vec(), Object::Array(vec![value]));
+            return Ok(());
+        }
+    };
+    match doc.get_object_mut(array_id) {
+        Ok(Object::Array(items)) => {
+            items.push(value);
+            Ok(())
+        }
+        _ => Err(AnalysisError::PdfError(format!(
+            "/{} does not reference an array",
+            String::from_utf8_lossy(key)
+        ))),
+    }
+}
+
+/// Splits text into lines of at most `max_chars` characters, breaking at
+/// whitespace and at explicit newlines. Words longer than a line are kept whole.
+pub fn wrap_text(text: &str, max_chars: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+    for paragraph in text.lines() {
+        let mut line = String::new();
+        for word in paragraph.split_whitespace() {
+            if !line.is_empty() && line.chars().count() + 1 + word.chars().count() > max_chars {
+                lines.push(std::mem::take(&mut line));
+            }
+            if !line.is_empty() {
+                line.push(' ');
+            }
+            line.push_str(word);
+        }
+        if !line.is_empty() {
+            lines.push(line);
+        }
+    }
+    lines
+}
+
+/// Number of average-width glyphs at `font_size` that fit in `width` points.
+pub fn max_chars_for_width(width: f64, font_size: f64) -> usize {
+    let glyph_width = font_size * AVERAGE_GLYPH_WIDTH;
+    if glyph_width <= 0.0 {
+        return usize::MAX;
+    }
+    ((width / glyph_width).floor() as usize).max(1)
+}
+
+/// The lines [`add_text_to_page`] draws `text` on: wrapped to `max_width`, or
+/// a single line without it.
+pub fn block_lines(text: &str, font_size: f64, max_width: Option<f64>) -> Vec<String> {
+    match max_width {
+        Some(max_width) => wrap_text(text, max_chars_for_width(max_width, font_size)),
+        None => vec![text.to_string()],
+    }
+}
+
+/// Baseline for the first line of a block whose last line should sit on `y`.
+/// Wrapped text flows downwards from its origin, so a block anchored at the
+/// bottom of a page is raised by the lines below the first.
+pub fn bottom_anchored_y(text: &str, y: f64, font_size: f64, max_width: Option<f64>) -> f64 {
+    let lines = block_lines(text, font_size, max_width).len();
+    y + lines.saturating_sub(1) as f64 * font_size * LINE_HEIGHT
+}
+
+/// Estimated width in points of `text` at `font_size`, in average-width glyphs.
+pub fn text_width(text: &str, font_size: f64) -> f64 {
+    text.chars().count() as f64 * font_size * AVERAGE_GLYPH_WIDTH
+}
+
+/// Positions text at `(x, y)` inside a text object, one `Tj` per wrapped line
+/// separated by `Td` line advances. Without `max_width` the text is shown as
+/// a single string.
+fn text_lines(text: &str, x: f64, y: f64, font_size: f64, max_width: Option<f64>) -> Vec<Operation> {
+    let mut operations = vec![Operation::new("Td", vec![x.into(), y.into()])];
+    let Some(max_width) = max_width else {
+        operations.push(Operation::new("Tj", vec![Object::string_literal(text)]));
+        return operations;
+    };
+    let leading = font_size * LINE_HEIGHT;
+    for (index, line) in wrap_text(text, max_chars_for_width(max_width, font_size)).into_iter().enumerate() {
+        if index > 0 {
+            operations.push(Operation::new("Td", vec![0.into(), (-leading).into()]));
+        }
+        operations.push(Operation::new("Tj", vec![Object::string_literal(line)]));
+    }
+    operations
+}
+
+/// Like [`text_lines`], with one `Tj` per word. Each word is moved past the
+/// previous one by its estimated width plus a space.
+fn fragmented_text_lines(text: &str, x: f64, y: f64, font_size: f64, max_width: Option<f64>) -> Vec<Operation> {
+    let lines = block_lines(text, font_size, max_width);
+    let glyph_width = font_size * AVERAGE_GLYPH_WIDTH;
+    let leading = font_size * LINE_HEIGHT;
+    let mut operations = vec![Operation::new("Td", vec![x.into(), y.into()])];
+    // `Td` is relative to the previous word, so a new line first steps back
+    // by everything the last line advanced.
+    let mut line_offset = 0.0;
+    for (index, line) in lines.iter().enumerate() {
+        if index > 0 {
+            operations.push(Operation::new("Td", vec![(-line_offset).into(), (-leading).into()]));
+            line_offset = 0.0;
+        }
+        let mut advance = 0.0;
+        for (word_index, word) in line.split_whitespace().enumerate() {
+            if word_index > 0 {
+                operations.push(Operation::new("Td", vec![advance.into(), 0.into()]));
+                line_offset += advance;
+            }
+            operations.push(Operation::new("Tj", vec![Object::string_literal(word)]));
+            advance = (word.chars().count() + 1) as f64 * glyph_width;
+        }
+    }
+    operations
+}
+
 /// Creates a blank PDF document.
 pub fn create_blank_pdf() -> Document {
     let mut doc = Document::with_version("1.4");
@@ -218,8 +603,7 @@ pub fn add_link_annotation(
     width: f64,
     height: f64,
 ) -> Result<()> {
-    let pages = doc.get_pages();
-    let page_id = *pages.get(&page_number).ok_or_else(|| AnalysisError::PdfError(format!("Page {} not found", page_number)))?;
+    let page_id = nth_page_id(doc, page_number)?;
 
     let rect = vec![x.into(), y.into(), (x + width).into(), (y + height).into()];
     
@@ -239,65 +623,628 @@ pub fn add_link_annotation(
 
     let annot_id = doc.add_object(annotation);
 
-    let page = doc.get_object_mut(page_id).unwrap().as_dict_mut().unwrap();
-    if !page.has(b"Annots") {
-        page.set("Annots", Object::Array(vec![]));
-    }
+    push_to_array(doc, page_id, b"Annots", Object::Reference(annot_id))
+}
 
-    if let Ok(Object::Array(annots)) = page.get_mut(b"Annots") {
-        annots.push(Object::Reference(annot_id));
-    }
+/// Adds a `/FreeText` annotation showing `text` to a page. `rect` is
+/// `[llx, lly, urx, ury]`; `opacity` becomes the `/CA` alpha, so 0.0 keeps
+/// the text out of sight while annotation extractors still read `/Contents`.
+pub fn add_freetext_annotation(
+    doc: &mut Document,
+    page_number: u32,
+    text: &str,
+    rect: [f64; 4],
+    opacity: f64,
+) -> Result<()> {
+    let page_id = nth_page_id(doc, page_number)?;
+
+    let annot_id = doc.add_object(dictionary! {
+        "Type" => "Annot",
+        "Subtype" => "FreeText",
+        "Rect" => rect.iter().map(|v| Object::Real(*v as f32)).collect::<Vec<_>>(),
+        "Contents" => Object::string_literal(text),
+        "DA" => Object::string_literal("/Helv 10 Tf 0 g"),
+        "CA" => Object::Real(opacity.clamp(0.0, 1.0) as f32),
+        "Border" => Object::Array(vec![0.into(), 0.into(), 0.into()]),
+        "P" => page_id,
+    });
+
+    push_to_array(doc, page_id, b"Annots", Object::Reference(annot_id))?;
 
     Ok(())
 }
 
-/// Adds a JavaScript action to the PDF's OpenAction.
+/// Annotation flag hiding a widget from display and printing.
+const ANNOT_FLAG_HIDDEN: i64 = 2;
+
+/// Adds a hidden AcroForm text field holding `value` to a page and registers
+/// it in the catalog's `/AcroForm /Fields`. `rect` is `[llx, lly, urx, ury]`;
+/// a zero-size rectangle keeps the widget off screen entirely.
+pub fn add_acroform_text_field(
+    doc: &mut Document,
+    page_number: u32,
+    name: &str,
+    value: &str,
+    rect: [f64; 4],
+) -> Result<()> {
+    let page_id = nth_page_id(doc, page_number)?;
+
+    let field_id = doc.add_object(dictionary! {
+        "Type" => "Annot",
+        "Subtype" => "Widget",
+        "FT" => "Tx",
+        "T" => Object::string_literal(name),
+        "V" => Object::string_literal(value),
+        "Rect" => rect.iter().map(|v| Object::Real(*v as f32)).collect::<Vec<_>>(),
+        "F" => ANNOT_FLAG_HIDDEN,
+        "P" => page_id,
+    });
+
+    push_to_array(doc, page_id, b"Annots", Object::Reference(field_id))?;
+
+    let catalog_id = catalog_id(doc)?;
+    let acroform_id = indirect_dict_entry(doc, catalog_id, b"AcroForm")?;
+    push_to_array(doc, acroform_id, b"Fields", Object::Reference(field_id))?;
+
+    Ok(())
+}
+
+/// Sets the catalog's `/OpenAction` to a JavaScript action running `js`.
+/// Readers only look for `/OpenAction` in the catalog, so it is not placed
+/// on the trailer.
 pub fn add_javascript_action(doc: &mut Document, js: &str) -> Result<()> {
     let js_action = doc.add_object(dictionary! {
+        "Type" => "Action",
         "S" => "JavaScript",
         "JS" => Object::String(js.into(), lopdf::StringFormat::Literal),
     });
 
-    doc.trailer.set("OpenAction", js_action);
+    let catalog_id = catalog_id(doc)?;
+    doc.get_dictionary_mut(catalog_id)
+        .map_err(|e| AnalysisError::PdfError(e.to_string()))?
+        .set("OpenAction", Object::Reference(js_action));
+    Ok(())
+}
+
+/// Sets the catalog's `/OpenAction` to a `/Launch` or `/GoToR` action
+/// pointing at `target`. Remote go-to actions open the target's first page.
+pub fn add_document_action(doc: &mut Document, kind: ActionKind, target: &str) -> Result<()> {
+    let mut action = dictionary! {
+        "Type" => "Action",
+        "S" => kind.subtype(),
+        "F" => Object::string_literal(target),
+    };
+    if kind == ActionKind::GoToRemote {
+        action.set("D", vec![Object::Integer(0), "Fit".into()]);
+    }
+    let action_id = doc.add_object(action);
+
+    let catalog_id = catalog_id(doc)?;
+    doc.get_dictionary_mut(catalog_id)
+        .map_err(|e| AnalysisError::PdfError(e.to_string()))?
+        .set("OpenAction", Object::Reference(action_id));
+    Ok(())
+}
+
+/// Serializes `doc` as an incremental update appended to `original`, the
+/// bytes it was loaded from. Only added or changed objects are written,
+/// followed by an xref section whose `/Prev` points at the original one, so
+/// the original bytes survive verbatim. Returns the new file and the number
+/// of appended objects.
+pub fn incremental_update(original: Vec<u8>, doc: &Document) -> Result<(Vec<u8>, usize)> {
+    let prev = Document::load_mem(&original).map_err(PdfLoadError::from)?;
+    let mut update = IncrementalDocument::create_from(original, prev);
+
+    let prev = update.get_prev_documents();
+    let changed: Vec<(ObjectId, Object)> = doc
+        .objects
+        .iter()
+        .filter(|(id, object)| prev.objects.get(id) != Some(*object))
+        .map(|(id, object)| (*id, object.clone()))
+        .collect();
+    let trailer_changes: Vec<(Vec<u8>, Object)> = doc
+        .trailer
+        .iter()
+        .filter(|(key, value)| key.as_slice() != b"Prev" && prev.trailer.get(key).ok() != Some(*value))
+        .map(|(key, value)| (key.clone(), value.clone()))
+        .collect();
+
+    let appended = changed.len();
+    update.new_document.max_id = update.new_document.max_id.max(doc.max_id);
+    for (id, object) in changed {
+        update.new_document.set_object(id, object);
+    }
+    for (key, value) in trailer_changes {
+        update.new_document.trailer.set(key, value);
+    }
+
+    let mut bytes = Vec::new();
+    update
+        .save_to(&mut bytes)
+        .map_err(|e| AnalysisError::PdfError(format!("Failed to save PDF: {}", e)))?;
+    Ok((bytes, appended))
+}
+
+/// Deflates every stream that has no `/Filter` yet and marks it
+/// `/FlateDecode`, leaving alone streams that opt out of compression and
+/// those for which `skip` returns true. Unlike [`lopdf::Stream::compress`],
+/// short streams are compressed too, so no plaintext is left behind.
+/// Returns how many streams were compressed.
+pub fn compress_streams(doc: &mut Document, skip: impl Fn(ObjectId, &Object) -> bool) -> Result<usize> {
+    use flate2::write::ZlibEncoder;
+    use flate2::Compression;
+    use std::io::Write;
+
+    let mut compressed = 0;
+    for (id, object) in doc.objects.iter_mut() {
+        if skip(*id, object) {
+            continue;
+        }
+        if let Object::Stream(stream) = object {
+            if stream.is_compressed() || !stream.allows_compression {
+                continue;
+            }
+            let mut encoder = ZlibEncoder::new(Vec::new(), Compression::best());
+            encoder.write_all(&stream.content)?;
+            let content = encoder.finish()?;
+            stream.dict.set("Filter", "FlateDecode");
+            stream.set_content(content);
+            compressed += 1;
+        }
+    }
+    Ok(compressed)
+}
+
+/// Loads a PDF from memory, decrypting it when it is password protected.
+/// Encryption is stripped from the returned document, so it saves in the
+/// clear unless passed to [`encrypt_document`]. The flag tells whether the
+/// input was encrypted.
+pub fn load_document(bytes: &[u8], password: Option<&str>) -> Result<(Document, bool)> {
+    let doc = Document::load_mem(bytes).map_err(PdfLoadError::from)?;
+    if !doc.is_encrypted() {
+        return Ok((doc, false));
+    }
+
+    // lopdf decrypts documents with an empty user password on load; anything
+    // else is left unparsed and has to be read again with the key.
+    let mut doc = if doc.encryption_state.is_some() {
+        doc
+    } else {
+        let password = password.ok_or(PdfLoadError::Encrypted)?;
+        decrypt_document(bytes, doc, password)?
+    };
+
+    if let Ok(encrypt_id) = doc.trailer.get(b"Encrypt").and_then(Object::as_reference) {
+        doc.objects.remove(&encrypt_id);
+    }
+    doc.trailer.remove(b"Encrypt");
+    doc.encryption_state = None;
+    Ok((doc, true))
+}
+
+/// Re-reads every object of an encrypted document from `bytes` and decrypts
+/// it, including objects packed into object streams.
+fn decrypt_document(bytes: &[u8], doc: Document, password: &str) -> Result<Document> {
+    doc.authenticate_password(password)
+        .map_err(|_| PdfLoadError::WrongPassword)?;
+    let state = EncryptionState::decode(&doc, password).map_err(|e| AnalysisError::PdfError(e.to_string()))?;
+    let encrypt_id = doc.trailer.get(b"Encrypt").and_then(Object::as_reference).ok();
+    let entries: Vec<(u32, XrefEntry)> = doc
+        .reference_table
+        .entries
+        .iter()
+        .map(|(number, entry)| (*number, entry.clone()))
+        .collect();
+
+    let reader = Reader {
+        buffer: bytes,
+        document: doc,
+        encryption_state: None,
+        raw_objects: BTreeMap::new(),
+    };
+    let mut objects = BTreeMap::new();
+    let mut containers = BTreeSet::new();
+    for (number, entry) in entries {
+        match entry {
+            XrefEntry::Normal { generation, .. } => {
+                let id = (number, generation);
+                let Ok(mut object) = reader.get_object(id, &mut HashSet::new()) else { continue };
+                if Some(id) != encrypt_id {
+                    encryption::decrypt_object(&state, id, &mut object)
+                        .map_err(|e| AnalysisError::PdfError(format!("Failed to decrypt object {} {}: {}", id.0, id.1, e)))?;
+                }
+                objects.insert(id, object);
+            }
+            XrefEntry::Compressed { container, .. } => {
+                containers.insert(container);
+            }
+            XrefEntry::Free | XrefEntry::UnusableFree => {}
+        }
+    }
+
+    // Object streams were decrypted as a whole above; unpack their contents.
+    for container in containers {
+        let Some(Object::Stream(stream)) = objects.get_mut(&(container, 0)) else { continue };
+        let Ok(object_stream) = ObjectStream::new(stream) else { continue };
+        for (id, object) in object_stream.objects {
+            objects.entry(id).or_insert(object);
+        }
+    }
+
+    let mut doc = reader.document;
+    doc.objects = objects;
+    Ok(doc)
+}
+
+/// Encrypts `doc` with AES-256 (PDF 2.0 standard security handler), using
+/// `password` as both the user and the owner password.
+pub fn encrypt_document(doc: &mut Document, password: &str) -> Result<()> {
+    let mut file_encryption_key = [0u8; 32];
+    rand::rng().fill(&mut file_encryption_key);
+    let crypt_filter: Arc<dyn CryptFilter> = Arc::new(Aes256CryptFilter);
+
+    let state = EncryptionState::try_from(EncryptionVersion::V5 {
+        encrypt_metadata: true,
+        crypt_filters: BTreeMap::from([(b"StdCF".to_vec(), crypt_filter)]),
+        file_encryption_key: &file_encryption_key,
+        stream_filter: b"StdCF".to_vec(),
+        string_filter: b"StdCF".to_vec(),
+        owner_password: password,
+        user_password: password,
+        permissions: Permissions::all(),
+    })
+    .map_err(|e| AnalysisError::PdfError(e.to_string()))?;
+    doc.encrypt(&state).map_err(|e| AnalysisError::PdfError(e.to_string()))
+}
+
+/// Page attributes a page may inherit from its ancestors in the page tree.
+const INHERITABLE_PAGE_KEYS: &[&[u8]] = &[b"Resources", b"MediaBox", b"CropBox", b"Rotate"];
+
+/// Appends every page of the PDF at `source_path` to the end of `target`,
+/// returning how many pages were added. The source objects are renumbered
+/// past the target's ids, and inherited page attributes are copied onto
+/// each page since the source page tree is not carried over.
+pub fn append_pages_from(target: &mut Document, source_path: &std::path::Path) -> Result<usize> {
+    let mut source = Document::load(source_path)
+        .map_err(|e| AnalysisError::PdfError(format!("Failed to load {}: {}", source_path.display(), e)))?;
+    source.renumber_objects_with(target.max_id + 1);
+
+    let target_pages_id = target
+        .catalog()
+        .and_then(|catalog| catalog.get(b"Pages"))
+        .and_then(Object::as_reference)
+        .map_err(|e| AnalysisError::PdfError(format!("Target PDF has no page tree: {}", e)))?;
+
+    let page_ids: Vec<ObjectId> = source.get_pages().into_values().collect();
+    for &page_id in &page_ids {
+        let mut inherited = Vec::new();
+        let mut parent = source.get_dictionary(page_id).and_then(|page| page.get(b"Parent")).and_then(Object::as_reference).ok();
+        while let Some(node_id) = parent {
+            let Ok(node) = source.get_dictionary(node_id) else { break };
+            for key in INHERITABLE_PAGE_KEYS {
+                if let Ok(value) = node.get(key)
+                    && !inherited.iter().any(|(k, _)| k == key)
+                {
+                    inherited.push((*key, value.clone()));
+                }
+            }
+            parent = node.get(b"Parent").and_then(Object::as_reference).ok();
+        }
+        let page = source
+            .get_object_mut(page_id)
+            .and_then(Object::as_dict_mut)
+            .map_err(|e| AnalysisError::PdfError(e.to_string()))?;
+        for (key, value) in inherited {
+            if !page.has(key) {
+                page.set(key, value);
+            }
+        }
+        page.set("Parent", Object::Reference(target_pages_id));
+    }
+
+    // The source catalog and page tree nodes would only be orphans.
+    for (id, object) in source.objects {
+        let is_tree_node = object
+            .as_dict()
+            .and_then(|dict| dict.get(b"Type"))
+            .and_then(Object::as_name)
+            .is_ok_and(|kind| kind == b"Catalog" || kind == b"Pages");
+        if !is_tree_node {
+            target.objects.insert(id, object);
+        }
+    }
+    target.max_id = target.max_id.max(source.max_id);
+
+    let pages = target
+        .get_object_mut(target_pages_id)
+        .and_then(Object::as_dict_mut)
+        .map_err(|e| AnalysisError::PdfError(e.to_string()))?;
+    let mut kids = pages.get(b"Kids").and_then(Object::as_array).cloned().unwrap_or_default();
+    kids.extend(page_ids.iter().map(|&id| Object::Reference(id)));
+    let count = pages.get(b"Count").and_then(Object::as_i64).unwrap_or(0);
+    pages.set("Kids", kids);
+    pages.set("Count", count + page_ids.len() as i64);
+    Ok(page_ids.len())
+}
+
+/// Attaches a file to the document and registers it in the catalog's
+/// `/Names /EmbeddedFiles` tree, keeping the tree's keys sorted.
+pub fn add_embedded_file(doc: &mut Document, name: &str, data: &[u8]) -> Result<()> {
+    let file_id = doc.add_object(lopdf::Stream::new(
+        dictionary! {
+            "Type" => "EmbeddedFile",
+            "Params" => dictionary! { "Size" => data.len() as i64 },
+        },
+        data.to_vec(),
+    ));
+    let filespec_id = doc.add_object(dictionary! {
+        "Type" => "Filespec",
+        "F" => Object::string_literal(name),
+        "UF" => Object::string_literal(name),
+        "EF" => dictionary! { "F" => file_id },
+    });
+
+    let catalog_id = catalog_id(doc)?;
+    let names_id = indirect_dict_entry(doc, catalog_id, b"Names")?;
+    let tree_id = indirect_dict_entry(doc, names_id, b"EmbeddedFiles")?;
+    let tree = doc
+        .get_dictionary_mut(tree_id)
+        .map_err(|e| AnalysisError::PdfError(e.to_string()))?;
+    if !tree.has(b"Names") {
+        tree.set("Names", Object::Array(vec![]));
+    }
+    if let Ok(Object::Array(entries)) = tree.get_mut(b"Names") {
+        let index = entries
+            .chunks(2)
+            .position(|pair| pair[0].as_str().is_ok_and(|key| key > name.as_bytes()))
+            .map_or(entries.len(), |pair| pair * 2);
+        entries.insert(index, Object::Reference(filespec_id));
+        entries.insert(index, Object::string_literal(name));
+    }
+
     Ok(())
 }
 
+/// Returns the object id of the document catalog.
+pub(crate) fn catalog_id(doc: &Document) -> Result<ObjectId> {
+    doc.trailer
+        .get(b"Root")
+        .and_then(Object::as_reference)
+        .map_err(|_| AnalysisError::PdfError("Document has no catalog".to_string()))
+}
+
+/// Returns the id of the dictionary stored under `key` in the dictionary
+/// `parent_id`, moving a direct dictionary (or creating an empty one) into
+/// its own object first.
+pub(crate) fn indirect_dict_entry(doc: &mut Document, parent_id: ObjectId, key: &[u8]) -> Result<ObjectId> {
+    let parent = doc
+        .get_dictionary(parent_id)
+        .map_err(|e| AnalysisError::PdfError(e.to_string()))?;
+    let existing = match parent.get(key) {
+        Ok(Object::Reference(id)) => return Ok(*id),
+        Ok(Object::Dictionary(dict)) => dict.clone(),
+        _ => dictionary! {},
+    };
+    let id = doc.add_object(existing);
+    doc.get_dictionary_mut(parent_id)
+        .map_err(|e| AnalysisError::PdfError(e.to_string()))?
+        .set(key.to_vec(), Object::Reference(id));
+    Ok(id)
+}
+
+/// Caps on decoded content-stream sizes, protecting against decompression
+/// bombs in untrusted PDFs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DecodeLimits {
+    /// Maximum decoded bytes for the content of a single page.
+    pub max_page_bytes: usize,
+    /// Maximum decoded bytes across all pages of a document.
+    pub max_total_bytes: usize,
+}
+
+impl Default for DecodeLimits {
+    fn default() -> Self {
+        Self {
+            max_page_bytes: 16 * 1024 * 1024,
+            max_total_bytes: 64 * 1024 * 1024,
+        }
+    }
+}
+
+/// Tracks decoded bytes against [`DecodeLimits`] while walking a document.
+#[derive(Debug, Clone)]
+pub struct DecodeBudget {
+    limits: DecodeLimits,
+    used: usize,
+}
+
+impl DecodeBudget {
+    /// Starts a fresh budget for one document.
+    pub fn new(limits: DecodeLimits) -> Self {
+        Self { limits, used: 0 }
+    }
+
+    /// Returns the decoded content of a page, failing with a `PdfError` as
+    /// soon as the per-page or total limit would be exceeded. FlateDecode
+    /// streams are inflated incrementally so oversized streams are never
+    /// fully materialized.
+    pub fn page_content(&mut self, doc: &Document, page_id: ObjectId) -> Result<Vec<u8>> {
+        let page_limit = self
+            .limits
+            .max_page_bytes
+            .min(self.limits.max_total_bytes.saturating_sub(self.used));
+        let mut content = Vec::new();
+        for stream_id in doc.get_page_contents(page_id) {
+            let Ok(stream) = doc.get_object(stream_id).and_then(Object::as_stream) else { continue };
+            // Streams are concatenated as if separated by whitespace, so a
+            // trailing `ET` cannot fuse with the next stream's `BT`.
+            if !content.is_empty() {
+                content.push(b'\n');
+            }
+            let remaining = page_limit.saturating_sub(content.len());
+            content.extend(decode_stream_bounded(stream, remaining)?);
+        }
+        self.used += content.len();
+        Ok(content)
+    }
+}
+
+/// Decodes a stream through its filters, erroring as soon as any stage's
+/// output would exceed `limit` bytes. Flate and LZW stages are inflated
+/// incrementally so oversized output is never fully materialized; the other
+/// stages cannot expand their input more than fourfold. A stream with a
+/// filter that cannot be decoded is returned as stored, like lopdf does.
+fn decode_stream_bounded(stream: &lopdf::Stream, limit: usize) -> Result<Vec<u8>> {
+    let exceeded = || AnalysisError::PdfError(format!("Decoded content stream exceeds the limit of {} bytes", limit));
+    let filters: Vec<&[u8]> = match stream.dict.get(b"Filter") {
+        Ok(Object::Name(name)) => vec![name.as_slice()],
+        Ok(Object::Array(names)) => names.iter().filter_map(|n| n.as_name().ok()).collect(),
+        _ => Vec::new(),
+    };
+    // One parameter dictionary per filter, or a single one shared by all.
+    let parms_for = |index: usize| match stream.dict.get(b"DecodeParms") {
+        Ok(Object::Array(parms)) => parms.get(index).and_then(|p| p.as_dict().ok()),
+        Ok(parms) => parms.as_dict().ok(),
+        Err(_) => None,
+    };
+
+    let mut decoded = stream.content.clone();
+    for (index, filter) in filters.iter().enumerate() {
+        let parms = parms_for(index);
+        decoded = match *filter {
+            b"FlateDecode" => apply_predictor(inflate_bounded(&decoded, limit)?, parms)?,
+            b"LZWDecode" => {
+                let early_change = parms
+                    .and_then(|p| p.get(b"EarlyChange").and_then(Object::as_i64).ok())
+                    .is_none_or(|v| v != 0);
+                apply_predictor(lzw_decode_bounded(&decoded, early_change, limit)?, parms)?
+            }
+            b"ASCII85Decode" => lopdf::Stream::new(dictionary! { "Filter" => "ASCII85Decode" }, decoded)
+                .decompressed_content()
+                .map_err(|e| AnalysisError::PdfError(format!("Failed to decode content stream: {}", e)))?,
+            b"ASCIIHexDecode" => ascii_hex_decode(&decoded),
+            _ => {
+                decoded = stream.content.clone();
+                break;
+            }
+        };
+        if decoded.len() > limit {
+            return Err(exceeded());
+        }
+    }
+    if decoded.len() > limit {
+        return Err(exceeded());
+    }
+    Ok(decoded)
+}
+
+/// Inflates zlib data, failing once the output would exceed `limit` bytes.
+fn inflate_bounded(data: &[u8], limit: usize) -> Result<Vec<u8>> {
+    use std::io::Read;
+
+    let mut inflated = Vec::new();
+    flate2::read::ZlibDecoder::new(data)
+        .take(limit as u64 + 1)
+        .read_to_end(&mut inflated)
+        .map_err(|e| AnalysisError::PdfError(format!("Failed to inflate content stream: {}", e)))?;
+    if inflated.len() > limit {
+        return Err(AnalysisError::PdfError(format!(
+            "Decoded content stream exceeds the limit of {} bytes",
+            limit
+        )));
+    }
+    Ok(inflated)
+}
+
+/// Decodes LZW data chunk by chunk, failing once the output would exceed
+/// `limit` bytes.
+fn lzw_decode_bounded(data: &[u8], early_change: bool, limit: usize) -> Result<Vec<u8>> {
+    use weezl::{decode::Decoder, BitOrder, LzwStatus};
+
+    let mut decoder = if early_change {
+        Decoder::with_tiff_size_switch(BitOrder::Msb, 8)
+    } else {
+        Decoder::new(BitOrder::Msb, 8)
+    };
+    let mut input = data;
+    let mut output = Vec::new();
+    let mut chunk = [0u8; 8192];
+    loop {
+        let result = decoder.decode_bytes(input, &mut chunk);
+        input = &input[result.consumed_in..];
+        output.extend_from_slice(&chunk[..result.consumed_out]);
+        if output.len() > limit {
+            return Err(AnalysisError::PdfError(format!(
+                "Decoded content stream exceeds the limit of {} bytes",
+                limit
+            )));
+        }
+        match result.status {
+            Ok(LzwStatus::Ok) => {}
+            Ok(LzwStatus::Done | LzwStatus::NoProgress) => return Ok(output),
+            Err(e) => return Err(AnalysisError::PdfError(format!("Failed to decode LZW content stream: {}", e))),
+        }
+    }
+}
+
+/// Reverses a PNG predictor (10–15) as lopdf does. PNG rows drop their tag
+/// byte, so the output is never larger than the input.
+fn apply_predictor(data: Vec<u8>, parms: Option<&lopdf::Dictionary>) -> Result<Vec<u8>> {
+    let Some(parms) = parms else { return Ok(data) };
+    let int = |key: &[u8], default: i64| parms.get(key).and_then(Object::as_i64).unwrap_or(default).max(1) as usize;
+    if !(10..=15).contains(&int(b"Predictor", 1)) {
+        return Ok(data);
+    }
+    let bytes_per_pixel = int(b"Colors", 1) * int(b"BitsPerComponent", 8).max(8) / 8;
+    lopdf::filters::png::decode_frame(&data, bytes_per_pixel, int(b"Columns", 1))
+        .map_err(|e| AnalysisError::PdfError(format!("Failed to reverse content stream predictor: {}", e)))
+}
+
+/// Decodes ASCIIHexDecode data up to its `>` marker; whitespace is skipped
+/// and an odd final digit is padded with 0.
+fn ascii_hex_decode(data: &[u8]) -> Vec<u8> {
+    let digits: Vec<u8> = data
+        .iter()
+        .take_while(|&&b| b != b'>')
+        .filter_map(|&b| (b as char).to_digit(16).map(|d| d as u8))
+        .collect();
+    digits
+        .chunks(2)
+        .map(|pair| pair[0] << 4 | pair.get(1).copied().unwrap_or(0))
+        .collect()
+}
+
 /// Extracts text from a PDF file (simplified).
 pub fn extract_text_from_pdf(path: &std::path::Path) -> Result<String> {
-    let doc = Document::load(path).map_err(|e| AnalysisError::PdfError(e.to_string()))?;
+    extract_text_from_pdf_with_limits(path, DecodeLimits::default())
+}
+
+/// Extracts text like [`extract_text_from_pdf`], with explicit decode limits.
+pub fn extract_text_from_pdf_with_limits(path: &std::path::Path, limits: DecodeLimits) -> Result<String> {
+    let doc = Document::load(path).map_err(PdfLoadError::from)?;
     let mut text = String::new();
+    let mut budget = DecodeBudget::new(limits);
+    let mut font_cache = cmap::FontMapCache::default();
 
     for page_id in doc.page_iter() {
-        let content = doc.get_page_content(page_id).map_err(|e| AnalysisError::PdfError(e.to_string()))?;
+        let content = budget.page_content(&doc, page_id)?;
         let content = Content::decode(&content).map_err(|e| AnalysisError::PdfError(e.to_string()))?;
+        let font_maps = cmap::page_font_maps(&doc, page_id, &mut font_cache);
+        let mut font_map: Option<&ToUnicodeMap> = None;
         
         for operation in content.operations {
             match operation.operator.as_str() {
+                "Tf" => {
+                    font_map = operation
+                        .operands
+                        .first()
+                        .and_then(|name| name.as_name().ok())
+                        .and_then(|name| font_maps.get(name).map(AsRef::as_ref));
+                }
                 "Tj" | "TJ" => {
                     // Extract text from Tj (show text) and TJ (show text with spacing)
-                    for operand in operation.operands {
-                        match operand {
-                            Object::String(bytes, _) => {
-                                if let Ok(s) = std::str::from_utf8(&bytes) {
-                                    text.push_str(s);
-                                } else {
-                                    // Try lossy
-                                    text.push_str(&String::from_utf8_lossy(&bytes));
-                                }
-                            }
-                            Object::Array(arr) => {
-                                for item in arr {
-                                    if let Object::String(bytes, _) = item {
-                                        if let Ok(s) = std::str::from_utf8(&bytes) {
-                                            text.push_str(s);
-                                        } else {
-                                            text.push_str(&String::from_utf8_lossy(&bytes));
-                                        }
-                                    }
-                                }
-                            }
-                            _ => {}
-                        }
+                    for operand in &operation.operands {
+                        text.push_str(&operand_text(operand, font_map));
                     }
                     text.push(' '); // Add space between text blocks
                 }
@@ -312,3 +1259,438 @@ pub fn extract_text_from_pdf(path: &std::path::Path) -> Result<String> {
 
     Ok(text)
 }
+
+/// Extracts text in reading order rather than content-stream order.
+///
+/// Text runs are positioned from the text and transformation matrices, then
+/// grouped into lines top-to-bottom and ordered left-to-right within a line.
+pub fn extract_text_layout_aware(path: &std::path::Path) -> Result<String> {
+    extract_text_layout_aware_with_limits(path, DecodeLimits::default())
+}
+
+/// Extracts text like [`extract_text_layout_aware`], with explicit decode limits.
+pub fn extract_text_layout_aware_with_limits(path: &std::path::Path, limits: DecodeLimits) -> Result<String> {
+    let doc = Document::load(path).map_err(PdfLoadError::from)?;
+    let mut text = String::new();
+    let mut budget = DecodeBudget::new(limits);
+    let mut font_cache = cmap::FontMapCache::default();
+
+    for page_id in doc.page_iter() {
+        let content = budget.page_content(&doc, page_id)?;
+        let content = Content::decode(&content).map_err(|e| AnalysisError::PdfError(e.to_string()))?;
+        let font_maps = cmap::page_font_maps(&doc, page_id, &mut font_cache);
+        let runs = collect_text_runs_with_fonts(&content.operations, &font_maps);
+
+        for line in group_lines(runs) {
+            text.push_str(&line);
+            text.push('\n');
+        }
+        text.push('\n'); // End of page
+    }
+
+    Ok(text)
+}
+
+/// Fraction of the font size within which two baselines count as one line.
+const LINE_TOLERANCE: f64 = 0.5;
+
+/// Orders runs into lines (top-to-bottom, then left-to-right) and joins each
+/// line, inserting a space where runs are visibly apart.
+pub(crate) fn group_lines(mut runs: Vec<TextRun>) -> Vec<String> {
+    runs.retain(|run| !run.text.trim().is_empty());
+    runs.sort_by(|a, b| b.y.total_cmp(&a.y).then(a.x.total_cmp(&b.x)));
+
+    let mut lines: Vec<Vec<TextRun>> = Vec::new();
+    for run in runs {
+        let tolerance = run.font_size.abs().max(1.0) * LINE_TOLERANCE;
+        match lines.last_mut() {
+            Some(line) if (line[0].y - run.y).abs() <= tolerance => line.push(run),
+            _ => lines.push(vec![run]),
+        }
+    }
+
+    lines
+        .into_iter()
+        .map(|mut line| {
+            line.sort_by(|a, b| a.x.total_cmp(&b.x));
+            let mut joined = String::new();
+            let mut end_x = f64::NEG_INFINITY;
+            for run in line {
+                let glyph_width = run.font_size.abs() * AVERAGE_GLYPH_WIDTH;
+                let apart = run.x - end_x > glyph_width * 0.5;
+                if apart && !joined.is_empty() && !joined.ends_with(' ') && !run.text.starts_with(' ') {
+                    joined.push(' ');
+                }
+                end_x = run.x + run.text.chars().count() as f64 * glyph_width;
+                joined.push_str(&run.text);
+            }
+            joined.trim_end().to_string()
+        })
+        .collect()
+}
+
+/// A single text-showing operation together with the state it was painted with.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TextRun {
+    /// Text shown by the operation (lossy-decoded).
+    pub text: String,
+    /// Horizontal origin of the run in default user space.
+    pub x: f64,
+    /// Vertical origin of the run in default user space.
+    pub y: f64,
+    /// Effective font size after applying the text and transformation matrices.
+    pub font_size: f64,
+    /// Text rendering mode set by `Tr` (3 means invisible).
+    pub render_mode: i64,
+    /// Non-stroking (fill) color as RGB in 0.0–1.0, converted from the
+    /// gray, RGB or CMYK value last set by `g`, `rg`, `k`, `sc` or `scn`.
+    /// `sc`/`scn` values in a space selected by `cs` other than the device
+    /// and CIE gray/RGB/CMYK spaces (e.g. Separation, Pattern or a named
+    /// resource) are not converted; such text keeps the space's initial
+    /// color, black.
+    pub fill_color: [f64; 3],
+    /// Index of the showing operation within the decoded content stream.
+    pub op_index: usize,
+}
+
+type Matrix = [f64; 6];
+
+const IDENTITY: Matrix = [1.0, 0.0, 0.0, 1.0, 0.0, 0.0];
+
+fn multiply(m1: &Matrix, m2: &Matrix) -> Matrix {
+    [
+        m1[0] * m2[0] + m1[1] * m2[2],
+        m1[0] * m2[1] + m1[1] * m2[3],
+        m1[2] * m2[0] + m1[3] * m2[2],
+        m1[2] * m2[1] + m1[3] * m2[3],
+        m1[4] * m2[0] + m1[5] * m2[2] + m2[4],
+        m1[4] * m2[1] + m1[5] * m2[3] + m2[5],
+    ]
+}
+
+/// Graphics state entries saved and restored by `q`/`Q`.
+#[derive(Clone)]
+struct GraphicsState {
+    ctm: Matrix,
+    font_size: f64,
+    leading: f64,
+    render_mode: i64,
+    fill_color: [f64; 3],
+    /// Whether `sc`/`scn` operands in the current fill color space map to RGB.
+    fill_space_mappable: bool,
+}
+
+/// Walks decoded content-stream operations and records every text-showing
+/// operation with its position, effective font size and rendering mode.
+pub fn collect_text_runs(operations: &[Operation]) -> Vec<TextRun> {
+    collect_text_runs_with_fonts(operations, &std::collections::HashMap::new())
+}
+
+/// Collects text runs like [`collect_text_runs`], decoding strings through
+/// the page's ToUnicode maps (see [`cmap::page_font_maps`]).
+pub fn collect_text_runs_with_fonts(
+    operations: &[Operation],
+    font_maps: &std::collections::HashMap<Vec<u8>, std::rc::Rc<ToUnicodeMap>>,
+) -> Vec<TextRun> {
+    let mut font_map: Option<&ToUnicodeMap> = None;
+    let mut runs = Vec::new();
+    let mut state = GraphicsState {
+        ctm: IDENTITY,
+        font_size: 0.0,
+        leading: 0.0,
+        render_mode: 0,
+        fill_color: [0.0; 3],
+        fill_space_mappable: true,
+    };
+    let mut stack: Vec<GraphicsState> = Vec::new();
+    let mut tm = IDENTITY;
+    let mut tlm = IDENTITY;
+
+    for (index, operation) in operations.iter().enumerate() {
+        let nums: Vec<f64> = operation.operands.iter().filter_map(as_number).collect();
+        match operation.operator.as_str() {
+            "q" => stack.push(state.clone()),
+            "Q" => {
+                if let Some(saved) = stack.pop() {
+                    state = saved;
+                }
+            }
+            "cm" if nums.len() == 6 => {
+                let m = [nums[0], nums[1], nums[2], nums[3], nums[4], nums[5]];
+                state.ctm = multiply(&m, &state.ctm);
+            }
+            "BT" => {
+                tm = IDENTITY;
+                tlm = IDENTITY;
+            }
+            "Tf" => {
+                if let Some(size) = nums.last() {
+                    state.font_size = *size;
+                }
+                font_map = operation
+                    .operands
+                    .first()
+                    .and_then(|name| name.as_name().ok())
+                    .and_then(|name| font_maps.get(name).map(AsRef::as_ref));
+            }
+            "Tr" => {
+                if let Some(mode) = nums.first() {
+                    state.render_mode = *mode as i64;
+                }
+            }
+            "g" | "rg" | "k" => {
+                if let Some(color) = fill_color_rgb(&nums) {
+                    state.fill_color = color;
+                    state.fill_space_mappable = true;
+                }
+            }
+            "cs" => {
+                let space = operation.operands.first().and_then(|name| name.as_name().ok());
+                state.fill_space_mappable =
+                    matches!(space, Some(b"DeviceGray" | b"DeviceRGB" | b"DeviceCMYK" | b"CalGray" | b"CalRGB"));
+                state.fill_color = [0.0; 3];
+            }
+            "sc" | "scn" if state.fill_space_mappable => {
+                if let Some(color) = fill_color_rgb(&nums) {
+                    state.fill_color = color;
+                }
+            }
+            "TL" => {
+                if let Some(leading) = nums.first() {
+                    state.leading = *leading;
+                }
+            }
+            "Td" | "TD" if nums.len() == 2 => {
+                if operation.operator == "TD" {
+                    state.leading = -nums[1];
+                }
+                tlm = multiply(&[1.0, 0.0, 0.0, 1.0, nums[0], nums[1]], &tlm);
+                tm = tlm;
+            }
+            "Tm" if nums.len() == 6 => {
+                tlm = [nums[0], nums[1], nums[2], nums[3], nums[4], nums[5]];
+                tm = tlm;
+            }
+            "T*" => {
+                tlm = multiply(&[1.0, 0.0, 0.0, 1.0, 0.0, -state.leading], &tlm);
+                tm = tlm;
+            }
+            "Tj" | "TJ" | "'" | "\"" => {
+                if operation.operator != "Tj" && operation.operator != "TJ" {
+                    tlm = multiply(&[1.0, 0.0, 0.0, 1.0, 0.0, -state.leading], &tlm);
+                    tm = tlm;
+                }
+                let text: String = operation
+                    .operands
+                    .iter()
+                    .filter(|o| matches!(o, Object::String(..) | Object::Array(_)))
+                    .map(|operand| operand_text(operand, font_map))
+                    .collect();
+                let trm = multiply(&tm, &state.ctm);
+                let scale = (trm[0] * trm[3] - trm[1] * trm[2]).abs().sqrt();
+                runs.push(TextRun {
+                    text,
+                    x: trm[4],
+                    y: trm[5],
+                    font_size: state.font_size * scale,
+                    render_mode: state.render_mode,
+                    fill_color: state.fill_color,
+                    op_index: index,
+                });
+            }
+            _ => {}
+        }
+    }
+
+    runs
+}
+
+/// Converts gray (1 component), RGB (3) or CMYK (4) color operands to RGB.
+/// Pattern names and other component counts yield `None`.
+fn fill_color_rgb(components: &[f64]) -> Option<[f64; 3]> {
+    match *components {
+        [gray] => Some([gray; 3]),
+        [r, g, b] => Some([r, g, b]),
+        [c, m, y, k] => Some([(1.0 - c) * (1.0 - k), (1.0 - m) * (1.0 - k), (1.0 - y) * (1.0 - k)]),
+        _ => None,
+    }
+}
+
+/// Finds the first text run whose text matches `section` case-insensitively,
+/// preferring a run that is exactly the heading over one that merely
+/// contains it. Returns the 1-based page number alongside the run.
+pub fn find_section_heading(doc: &Document, section: &str) -> Result<Option<(u32, TextRun)>> {
+    let needle = section.trim().to_lowercase();
+    if needle.is_empty() {
+        return Ok(None);
+    }
+    let mut budget = DecodeBudget::new(DecodeLimits::default());
+    let mut font_cache = cmap::FontMapCache::default();
+    let mut partial = None;
+    for (page_number, page_id) in doc.get_pages() {
+        let content = budget.page_content(doc, page_id)?;
+        let content = Content::decode(&content).map_err(|e| AnalysisError::PdfError(e.to_string()))?;
+        let font_maps = cmap::page_font_maps(doc, page_id, &mut font_cache);
+        for run in collect_text_runs_with_fonts(&content.operations, &font_maps) {
+            let text = run.text.trim().to_lowercase();
+            if text == needle {
+                return Ok(Some((page_number, run)));
+            }
+            if partial.is_none() && text.contains(&needle) {
+                partial = Some((page_number, run));
+            }
+        }
+    }
+    Ok(partial)
+}
+
+/// Returns the MediaBox of a page as `[llx, lly, urx, ury]`, following the
+/// `Parent` chain for inherited values and falling back to A4.
+pub fn page_media_box(doc: &Document, page_id: ObjectId) -> [f64; 4] {
+    let mut current = Some(page_id);
+    while let Some(id) = current {
+        let Ok(dict) = doc.get_dictionary(id) else { break };
+        if let Ok(media_box) = dict.get(b"MediaBox")
+            && let Ok(values) = resolve(doc, media_box).as_array()
+        {
+            let nums: Vec<f64> = values.iter().filter_map(|v| as_number(resolve(doc, v))).collect();
+            if nums.len() == 4 {
+                return [
+                    nums[0].min(nums[2]),
+                    nums[1].min(nums[3]),
+                    nums[0].max(nums[2]),
+                    nums[1].max(nums[3]),
+                ];
+            }
+        }
+        current = dict.get(b"Parent").and_then(|p| p.as_reference()).ok();
+    }
+    [0.0, 0.0, 595.0, 842.0]
+}
+
+/// Average Helvetica glyph width as a fraction of the font size.
+const AVERAGE_GLYPH_WIDTH: f64 = 0.5;
+
+/// Line height as a multiple of the font size.
+const LINE_HEIGHT: f64 = 1.2;
+
+/// A block of text written into a page by the mutator.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct InjectionRecord {
+    /// 1-based page number the text was written to.
+    pub page_number: u32,
+    /// The injected text.
+    pub text: String,
+    /// Horizontal origin of the block.
+    pub x: f64,
+    /// Vertical origin (baseline of the first line) of the block.
+    pub y: f64,
+    /// Font size the block was written with.
+    pub font_size: f64,
+    /// Width the text was wrapped to. `None` estimates wrapping at the right
+    /// edge of the page.
+    pub max_width: Option<f64>,
+}
+
+impl InjectionRecord {
+    /// Estimates the block's bounding box as `[llx, lly, urx, ury]`, with the
+    /// text flowing downwards from the first line. Without `max_width` it is
+    /// assumed to wrap at the right edge of `media_box`.
+    pub fn bounding_box(&self, media_box: &[f64; 4]) -> [f64; 4] {
+        if self.max_width.is_some() {
+            let lines = block_lines(&self.text, self.font_size, self.max_width);
+            let width = lines.iter().map(|line| text_width(line, self.font_size)).fold(0.0, f64::max);
+            let height = lines.len().max(1) as f64 * self.font_size * LINE_HEIGHT;
+            return [self.x, self.y + self.font_size - height, self.x + width, self.y + self.font_size];
+        }
+        let glyph_width = self.font_size * AVERAGE_GLYPH_WIDTH;
+        let text_width = self.text.chars().count() as f64 * glyph_width;
+        let available = (media_box[2] - self.x).max(glyph_width);
+        let lines = (text_width / available).ceil().max(1.0);
+        let width = text_width.min(available);
+        let height = lines * self.font_size * LINE_HEIGHT;
+        [self.x, self.y + self.font_size - height, self.x + width, self.y + self.font_size]
+    }
+}
+
+/// Returns the fraction (0.0-1.0) of the page area covered by the injected
+/// blocks. Boxes are clipped to the MediaBox, so off-page text adds nothing;
+/// with several pages the ratio is taken over the pages that were touched.
+pub fn injection_coverage(doc: &Document, records: &[InjectionRecord]) -> f64 {
+    let mut covered = 0.0;
+    let mut page_area = 0.0;
+    let mut pages_seen = Vec::new();
+
+    for record in records {
+        let Ok(page_id) = nth_page_id(doc, record.page_number) else { continue };
+        let media_box = page_media_box(doc, page_id);
+        if !pages_seen.contains(&record.page_number) {
+            pages_seen.push(record.page_number);
+            page_area += (media_box[2] - media_box[0]) * (media_box[3] - media_box[1]);
+        }
+        let bbox = record.bounding_box(&media_box);
+        let width = (bbox[2].min(media_box[2]) - bbox[0].max(media_box[0])).max(0.0);
+        let height = (bbox[3].min(media_box[3]) - bbox[1].max(media_box[1])).max(0.0);
+        covered += width * height;
+    }
+
+    if page_area <= 0.0 {
+        return 0.0;
+    }
+    (covered / page_area).min(1.0)
+}
+
+/// Follows an indirect reference, returning the object itself otherwise.
+pub(crate) fn resolve<'a>(doc: &'a Document, object: &'a Object) -> &'a Object {
+    match object {
+        Object::Reference(id) => doc.get_object(*id).unwrap_or(object),
+        _ => object,
+    }
+}
+
+/// Reads an integer or real operand as `f64`.
+pub(crate) fn as_number(object: &Object) -> Option<f64> {
+    match object {
+        Object::Integer(i) => Some(*i as f64),
+        Object::Real(r) => Some(*r as f64),
+        _ => None,
+    }
+}
+
+/// `TJ` adjustments (thousandths of text space) larger than this are treated
+/// as word breaks, as viewers do when reconstructing text.
+const TJ_WORD_GAP: f64 = 100.0;
+
+/// Decodes the string payload of a `Tj`/`TJ` operand, through the current
+/// font's ToUnicode map when it has one.
+fn operand_text(operand: &Object, font_map: Option<&ToUnicodeMap>) -> String {
+    match operand {
+        Object::String(bytes, _) => decode_bytes(bytes, font_map),
+        Object::Array(items) => {
+            let mut text = String::new();
+            for item in items {
+                match item {
+                    Object::String(bytes, _) => text.push_str(&decode_bytes(bytes, font_map)),
+                    other => {
+                        let gap = as_number(other).is_some_and(|adjustment| adjustment.abs() > TJ_WORD_GAP);
+                        if gap && !text.is_empty() && !text.ends_with(' ') {
+                            text.push(' ');
+                        }
+                    }
+                }
+            }
+            text
+        }
+        _ => String::new(),
+    }
+}
+
+fn decode_bytes(bytes: &[u8], font_map: Option<&ToUnicodeMap>) -> String {
+    if let Some(map) = font_map {
+        return map.decode(bytes);
+    }
+    match std::str::from_utf8(bytes) {
+        Ok(s) => s.to_string(),
+        Err(_) => String::from_utf8_lossy(bytes).into_owned(),
+    }
+}
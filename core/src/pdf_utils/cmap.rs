@@ -0,0 +1,360 @@
+use super::{decode_stream_bounded, resolve};
+use crate::{AnalysisError, Result};
+use lopdf::{Dictionary, Document, Object, ObjectId};
+use std::collections::HashMap;
+use std::rc::Rc;
+
+/// Upper bound on the decoded size of a single ToUnicode CMap stream.
+const MAX_CMAP_BYTES: usize = 1024 * 1024;
+
+/// Upper bound on the codes a single `bfrange` entry may map. The CMap spec
+/// keeps a range within one last-byte run, so longer ranges are clamped.
+const MAX_BFRANGE_CODES: u32 = 256;
+
+/// Upper bound on the mappings one CMap may define; two-byte codes need at
+/// most 65,536.
+const MAX_CMAP_ENTRIES: usize = 1 << 17;
+
+/// Upper bound on the mappings parsed across all CMaps of one document.
+const MAX_DOCUMENT_CMAP_ENTRIES: usize = 1 << 20;
+
+/// Character-code to Unicode mapping read from a font's `/ToUnicode` CMap.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ToUnicodeMap {
+    map: HashMap<Vec<u8>, String>,
+    /// Code lengths (in bytes) present in the map, longest first.
+    code_lengths: Vec<usize>,
+}
+
+impl ToUnicodeMap {
+    /// Parses the `bfchar` and `bfrange` sections of a CMap program, failing
+    /// once it defines more than 131,072 mappings.
+    pub fn parse(cmap: &[u8]) -> Result<Self> {
+        Self::parse_with_limit(cmap, MAX_CMAP_ENTRIES)
+    }
+
+    fn parse_with_limit(cmap: &[u8], max_entries: usize) -> Result<Self> {
+        let text = String::from_utf8_lossy(cmap);
+        let tokens = tokenize(&text);
+        let mut map = HashMap::new();
+        let insert = |map: &mut HashMap<Vec<u8>, String>, code: Vec<u8>, target: String| {
+            map.insert(code, target);
+            if map.len() > max_entries {
+                return Err(AnalysisError::PdfError(format!(
+                    "ToUnicode CMap defines more than {} mappings",
+                    max_entries
+                )));
+            }
+            Ok(())
+        };
+
+        let mut i = 0;
+        while i < tokens.len() {
+            match tokens[i] {
+                Token::Keyword("beginbfchar") => {
+                    i += 1;
+                    while i + 1 < tokens.len() && tokens[i] != Token::Keyword("endbfchar") {
+                        if let (Token::Hex(src), Token::Hex(dst)) = (&tokens[i], &tokens[i + 1]) {
+                            insert(&mut map, src.clone(), utf16be(dst))?;
+                        }
+                        i += 2;
+                    }
+                }
+                Token::Keyword("beginbfrange") => {
+                    i += 1;
+                    while i + 2 < tokens.len() && tokens[i] != Token::Keyword("endbfrange") {
+                        let (Token::Hex(lo), Token::Hex(hi)) = (&tokens[i], &tokens[i + 1]) else {
+                            i += 1;
+                            continue;
+                        };
+                        let start = code_value(lo);
+                        let end = code_value(hi).min(start.saturating_add(MAX_BFRANGE_CODES - 1));
+                        match &tokens[i + 2] {
+                            Token::Hex(dst) => {
+                                // Each code maps to `dst` with its last byte
+                                // advanced; the increment never carries.
+                                let mut target = dst.clone();
+                                for code in start..=end {
+                                    insert(&mut map, code_bytes(code, lo.len()), utf16be(&target))?;
+                                    let Some(last) = target.last_mut() else { break };
+                                    let Some(next) = last.checked_add(1) else { break };
+                                    *last = next;
+                                }
+                                i += 3;
+                            }
+                            Token::ArrayStart => {
+                                i += 3;
+                                let mut code = Some(start);
+                                while i < tokens.len() && tokens[i] != Token::ArrayEnd {
+                                    if let (Token::Hex(dst), Some(current)) = (&tokens[i], code) {
+                                        if current <= end {
+                                            insert(&mut map, code_bytes(current, lo.len()), utf16be(dst))?;
+                                        }
+                                        code = current.checked_add(1);
+                                    }
+                                    i += 1;
+                                }
+                                i += 1;
+                            }
+                            _ => i += 3,
+                        }
+                    }
+                }
+                _ => {}
+            }
+            i += 1;
+        }
+
+        let mut code_lengths: Vec<usize> = map.keys().map(Vec::len).collect();
+        code_lengths.sort_unstable_by(|a, b| b.cmp(a));
+        code_lengths.dedup();
+        Ok(ToUnicodeMap { map, code_lengths })
+    }
+
+    /// Returns true if the CMap defined no mappings.
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    /// Maps a shown string to Unicode, matching the longest code first.
+    /// Unmapped codes become U+FFFD.
+    pub fn decode(&self, bytes: &[u8]) -> String {
+        let shortest = self.code_lengths.last().copied().unwrap_or(1);
+        let mut text = String::new();
+        let mut pos = 0;
+        while pos < bytes.len() {
+            let matched = self.code_lengths.iter().find_map(|&len| {
+                let code = bytes.get(pos..pos + len)?;
+                self.map.get(code).map(|s| (len, s))
+            });
+            match matched {
+                Some((len, s)) => {
+                    text.push_str(s);
+                    pos += len;
+                }
+                None => {
+                    text.push(char::REPLACEMENT_CHARACTER);
+                    pos += shortest;
+                }
+            }
+        }
+        text
+    }
+}
+
+/// ToUnicode maps already parsed from one document, keyed by the CMap
+/// stream's object ID, so a font shared by many pages is parsed once.
+#[derive(Debug, Default)]
+pub struct FontMapCache {
+    parsed: HashMap<ObjectId, Option<Rc<ToUnicodeMap>>>,
+    /// Mappings parsed so far, checked against `MAX_DOCUMENT_CMAP_ENTRIES`.
+    entries: usize,
+}
+
+impl FontMapCache {
+    /// Parses a ToUnicode stream within what is left of the document's
+    /// mapping budget. `None` when it cannot be decoded, maps nothing or
+    /// exceeds the budget.
+    fn load(&mut self, doc: &Document, to_unicode: &Object) -> Option<Rc<ToUnicodeMap>> {
+        let stream = resolve(doc, to_unicode).as_stream().ok()?;
+        let cmap = decode_stream_bounded(stream, MAX_CMAP_BYTES).ok()?;
+        let remaining = MAX_DOCUMENT_CMAP_ENTRIES.saturating_sub(self.entries);
+        let map = ToUnicodeMap::parse_with_limit(&cmap, MAX_CMAP_ENTRIES.min(remaining)).ok()?;
+        self.entries += map.map.len();
+        (!map.is_empty()).then(|| Rc::new(map))
+    }
+}
+
+/// Reads the ToUnicode maps of every font in the page's resources, keyed by
+/// resource name (e.g. `F1`). Fonts without a usable map are omitted.
+pub fn page_font_maps(doc: &Document, page_id: ObjectId, cache: &mut FontMapCache) -> HashMap<Vec<u8>, Rc<ToUnicodeMap>> {
+    let mut maps = HashMap::new();
+    let Some(fonts) = page_fonts(doc, page_id) else { return maps };
+
+    for (name, font) in fonts.iter() {
+        let Ok(font) = resolve(doc, font).as_dict() else { continue };
+        let Ok(to_unicode) = font.get(b"ToUnicode") else { continue };
+        let map = match to_unicode.as_reference() {
+            Ok(id) => match cache.parsed.get(&id) {
+                Some(map) => map.clone(),
+                None => {
+                    let map = cache.load(doc, to_unicode);
+                    cache.parsed.insert(id, map.clone());
+                    map
+                }
+            },
+            Err(_) => cache.load(doc, to_unicode),
+        };
+        if let Some(map) = map {
+            maps.insert(name.clone(), map);
+        }
+    }
+    maps
+}
+
+/// Finds the `/Font` resource dictionary of a page, following inheritance.
+fn page_fonts(doc: &Document, page_id: ObjectId) -> Option<&Dictionary> {
+    let mut current = Some(page_id);
+    while let Some(id) = current {
+        let dict = doc.get_dictionary(id).ok()?;
+        if let Ok(resources) = dict.get(b"Resources") {
+            let resources = resolve(doc, resources).as_dict().ok()?;
+            return resolve(doc, resources.get(b"Font").ok()?).as_dict().ok();
+        }
+        current = dict.get(b"Parent").and_then(Object::as_reference).ok();
+    }
+    None
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token<'a> {
+    Hex(Vec<u8>),
+    ArrayStart,
+    ArrayEnd,
+    Keyword(&'a str),
+}
+
+fn tokenize(text: &str) -> Vec<Token<'_>> {
+    let mut tokens = Vec::new();
+    let mut rest = text;
+    while let Some(c) = rest.chars().next() {
+        match c {
+            '<' if !rest.starts_with("<<") => {
+                let end = rest.find('>').unwrap_or(rest.len());
+                let hex: String = rest[1..end].chars().filter(|c| c.is_ascii_hexdigit()).collect();
+                tokens.push(Token::Hex(hex_bytes(&hex)));
+                rest = rest.get(end + 1..).unwrap_or("");
+            }
+            '[' => {
+                tokens.push(Token::ArrayStart);
+                rest = &rest[1..];
+            }
+            ']' => {
+                tokens.push(Token::ArrayEnd);
+                rest = &rest[1..];
+            }
+            '%' => rest = rest.find('\n').map_or("", |end| &rest[end..]),
+            c if c.is_whitespace() => rest = &rest[c.len_utf8()..],
+            _ => {
+                let end = rest
+                    .find(|c: char| c.is_whitespace() || "<>[]%".contains(c))
+                    .unwrap_or(rest.len())
+                    .max(c.len_utf8());
+                tokens.push(Token::Keyword(&rest[..end]));
+                rest = &rest[end..];
+            }
+        }
+    }
+    tokens
+}
+
+fn hex_bytes(hex: &str) -> Vec<u8> {
+    // An odd trailing digit is padded with 0, as the PDF spec requires.
+    let padded = if hex.len() % 2 == 1 { format!("{}0", hex) } else { hex.to_string() };
+    (0..padded.len())
+        .step_by(2)
+        .filter_map(|i| u8::from_str_radix(&padded[i..i + 2], 16).ok())
+        .collect()
+}
+
+fn code_value(bytes: &[u8]) -> u32 {
+    bytes.iter().fold(0u32, |acc, b| (acc << 8) | *b as u32)
+}
+
+fn code_bytes(code: u32, len: usize) -> Vec<u8> {
+    let bytes = code.to_be_bytes();
+    bytes[bytes.len().saturating_sub(len)..].to_vec()
+}
+
+fn utf16be(bytes: &[u8]) -> String {
+    let units: Vec<u16> = bytes
+        .chunks(2)
+        .map(|pair| match pair {
+            [hi, lo] => u16::from_be_bytes([*hi, *lo]),
+            [single] => *single as u16,
+            _ => 0,
+        })
+        .collect();
+    String::from_utf16_lossy(&units)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_bfchar_and_bfrange() {
+        let cmap = b"/CIDInit /ProcSet findresource begin
+            1 begincodespacerange <0000> <FFFF> endcodespacerange
+            2 beginbfchar
+            <0003> <0020>
+            <0011> <00E9>
+            endbfchar
+            2 beginbfrange
+            <0024> <0026> <0041>
+            <0030> <0031> [<0078> <0079>]
+            endbfrange
+            endcmap";
+        let map = ToUnicodeMap::parse(cmap).unwrap();
+
+        assert_eq!(map.decode(&[0x00, 0x24, 0x00, 0x25, 0x00, 0x03, 0x00, 0x26]), "AB C");
+        assert_eq!(map.decode(&[0x00, 0x11, 0x00, 0x30, 0x00, 0x31]), "\u{e9}xy");
+        assert_eq!(map.decode(&[0x00, 0x99]), "\u{fffd}");
+    }
+
+    #[test]
+    fn test_parse_hostile_bfrange() {
+        let cmap = b"2 beginbfrange
+            <00000000> <FFFFFFFF> <0000>
+            <FFFFFFFE> <FFFFFFFF> <FFFFFFFF>
+            <0001> <0002> <00112233445566778899>
+            <FFFF> <FFFF> [<0041> <0042>]
+            endbfrange";
+        let map = ToUnicodeMap::parse(cmap).unwrap();
+
+        // The oversized range is clamped rather than expanded to 2^32 codes.
+        assert!(map.map.len() <= 4 * MAX_BFRANGE_CODES as usize);
+        assert_eq!(map.decode(&[0x00, 0x00, 0x00, 0x41]), "A");
+        assert!(map.map.contains_key(&vec![0xFF, 0xFF, 0xFF, 0xFE]));
+        assert_eq!(map.decode(&[0x00, 0x02]).chars().count(), 5);
+        assert_eq!(map.decode(&[0xFF, 0xFF]), "A");
+    }
+
+    #[test]
+    fn test_parse_rejects_too_many_mappings() {
+        // 1,024 full ranges of 256 codes each, well under MAX_CMAP_BYTES.
+        let mut cmap = String::from("1024 beginbfrange\n");
+        for high in 0..1024u32 {
+            cmap.push_str(&format!("<{:06X}> <{:06X}> <0000>\n", high << 8, (high << 8) | 0xFF));
+        }
+        cmap.push_str("endbfrange");
+        assert!(cmap.len() < MAX_CMAP_BYTES);
+
+        let err = ToUnicodeMap::parse(cmap.as_bytes()).unwrap_err();
+        assert!(err.to_string().contains("more than 131072 mappings"), "{}", err);
+        assert_eq!(ToUnicodeMap::parse_with_limit(cmap.as_bytes(), 1 << 18).unwrap().map.len(), 1 << 18);
+    }
+
+    #[test]
+    fn test_page_font_maps_parse_shared_cmap_once() {
+        use lopdf::{dictionary, Stream};
+
+        let mut doc = super::super::create_blank_pdf();
+        let cmap_id = doc.add_object(Stream::new(
+            dictionary! {},
+            b"1 beginbfchar <01> <0041> endbfchar".to_vec(),
+        ));
+        let font_id = doc.add_object(dictionary! { "Type" => "Font", "ToUnicode" => cmap_id });
+        let page_id = super::super::nth_page_id(&doc, 1).unwrap();
+        doc.get_object_mut(page_id)
+            .and_then(Object::as_dict_mut)
+            .unwrap()
+            .set("Resources", dictionary! { "Font" => dictionary! { "F9" => font_id } });
+
+        let mut cache = FontMapCache::default();
+        let first = page_font_maps(&doc, page_id, &mut cache);
+        let second = page_font_maps(&doc, page_id, &mut cache);
+        assert!(Rc::ptr_eq(&first[b"F9".as_slice()], &second[b"F9".as_slice()]));
+        assert_eq!(cache.entries, 1);
+    }
+}
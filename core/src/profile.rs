@@ -1,7 +1,9 @@
 use serde::{Deserialize, Serialize};
 
+mod json_resume;
+
 /// Contact information for the user.
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
 pub struct ContactInfo {
     /// Email address.
     pub email: Option<String>,
@@ -168,7 +170,7 @@ pub struct AiAtsMetadata {
 }
 
 /// The main user profile structure, aggregating all personal and professional data.
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
 pub struct UserProfile {
     /// Unique identifier for the profile.
     pub id: String,
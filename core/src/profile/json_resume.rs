@@ -0,0 +1,344 @@
+use super::{Certification, ContactInfo, Education, Experience, Language, Project, Skill, UserProfile};
+use crate::{AnalysisError, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+// Serde mirror of the JSON Resume schema (https://jsonresume.org/schema),
+// limited to the sections we map. Every field is optional there.
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+struct JsonResume {
+    basics: Basics,
+    work: Vec<Work>,
+    education: Vec<JsonEducation>,
+    skills: Vec<JsonSkill>,
+    projects: Vec<JsonProject>,
+    certificates: Vec<Certificate>,
+    languages: Vec<JsonLanguage>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+struct Basics {
+    #[serde(skip_serializing_if = "String::is_empty")]
+    name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    label: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    email: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    phone: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    summary: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    location: Option<Location>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    profiles: Vec<SocialProfile>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+struct Location {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    address: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    city: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    region: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    country_code: Option<String>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+struct SocialProfile {
+    network: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    username: Option<String>,
+    url: String,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+struct Work {
+    /// Employer; older documents call it `company`.
+    #[serde(alias = "company")]
+    name: String,
+    position: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    location: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    start_date: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    end_date: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    summary: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    highlights: Vec<String>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+struct JsonEducation {
+    institution: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    area: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    study_type: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    start_date: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    end_date: Option<String>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+struct JsonSkill {
+    name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    level: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    keywords: Vec<String>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+struct JsonProject {
+    name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    url: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    keywords: Vec<String>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+struct Certificate {
+    name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    date: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    issuer: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    url: Option<String>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+struct JsonLanguage {
+    language: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    fluency: Option<String>,
+}
+
+impl UserProfile {
+    /// Builds a profile from a JSON Resume document. Missing sections become
+    /// empty lists; a skill group's keywords become individual skills
+    /// categorized under the group name.
+    pub fn from_json_resume(value: Value) -> Result<UserProfile> {
+        let resume: JsonResume = serde_json::from_value(value)
+            .map_err(|e| AnalysisError::JsonError(format!("invalid JSON Resume: {}", e)))?;
+        let basics = resume.basics;
+
+        let mut contact = ContactInfo {
+            email: basics.email,
+            phone: basics.phone,
+            websites: basics.url.into_iter().collect(),
+            ..Default::default()
+        };
+        for profile in basics.profiles {
+            match profile.network.to_lowercase().as_str() {
+                "linkedin" => contact.linkedin = Some(profile.url),
+                "github" => contact.github = Some(profile.url),
+                _ => contact.websites.push(profile.url),
+            }
+        }
+        let location = basics.location.and_then(|location| {
+            let parts: Vec<String> = [location.city, location.region, location.country_code]
+                .into_iter()
+                .flatten()
+                .filter(|part| !part.is_empty())
+                .collect();
+            if parts.is_empty() { location.address } else { Some(parts.join(", ")) }
+        });
+
+        Ok(UserProfile {
+            name: basics.name,
+            headline: basics.label,
+            location,
+            summary: basics.summary,
+            contact,
+            experience: resume
+                .work
+                .into_iter()
+                .map(|work| Experience {
+                    title: work.position,
+                    company: work.name,
+                    location: work.location,
+                    start_date: work.start_date,
+                    end_date: work.end_date,
+                    summary: work.summary,
+                    bullets: work.highlights,
+                    tech_stack: Vec::new(),
+                })
+                .collect(),
+            education: resume
+                .education
+                .into_iter()
+                .map(|edu| Education {
+                    institution: edu.institution,
+                    degree: edu.study_type,
+                    field_of_study: edu.area,
+                    start_date: edu.start_date,
+                    end_date: edu.end_date,
+                    summary: None,
+                })
+                .collect(),
+            skills: resume
+                .skills
+                .into_iter()
+                .flat_map(|group| {
+                    if group.keywords.is_empty() {
+                        vec![Skill { name: group.name, category: None, proficiency: group.level }]
+                    } else {
+                        group
+                            .keywords
+                            .into_iter()
+                            .map(|keyword| Skill {
+                                name: keyword,
+                                category: Some(group.name.clone()),
+                                proficiency: group.level.clone(),
+                            })
+                            .collect()
+                    }
+                })
+                .collect(),
+            projects: resume
+                .projects
+                .into_iter()
+                .map(|project| Project {
+                    name: project.name,
+                    description: project.description,
+                    link: project.url,
+                    technologies: project.keywords,
+                })
+                .collect(),
+            certifications: resume
+                .certificates
+                .into_iter()
+                .map(|cert| Certification { name: cert.name, issuer: cert.issuer, date: cert.date, link: cert.url })
+                .collect(),
+            languages: resume
+                .languages
+                .into_iter()
+                .map(|language| Language { name: language.language, proficiency: language.fluency })
+                .collect(),
+            ..Default::default()
+        })
+    }
+
+    /// Serializes the profile as a JSON Resume document. Skills sharing a
+    /// category are grouped back into one entry with keywords; fields JSON
+    /// Resume has no place for (publications, volunteering, metadata) are dropped.
+    pub fn to_json_resume(&self) -> Value {
+        let mut profiles = Vec::new();
+        if let Some(url) = &self.contact.linkedin {
+            profiles.push(SocialProfile { network: "LinkedIn".into(), username: None, url: url.clone() });
+        }
+        if let Some(url) = &self.contact.github {
+            profiles.push(SocialProfile { network: "GitHub".into(), username: None, url: url.clone() });
+        }
+        let location = self.location.clone().or_else(|| self.contact.location.clone());
+
+        let mut skills: Vec<JsonSkill> = Vec::new();
+        for skill in &self.skills {
+            match &skill.category {
+                Some(category) => match skills.iter_mut().find(|group| &group.name == category && !group.keywords.is_empty()) {
+                    Some(group) => group.keywords.push(skill.name.clone()),
+                    None => skills.push(JsonSkill {
+                        name: category.clone(),
+                        level: skill.proficiency.clone(),
+                        keywords: vec![skill.name.clone()],
+                    }),
+                },
+                None => skills.push(JsonSkill { name: skill.name.clone(), level: skill.proficiency.clone(), keywords: Vec::new() }),
+            }
+        }
+
+        let resume = JsonResume {
+            basics: Basics {
+                name: self.name.clone(),
+                label: self.headline.clone(),
+                email: self.contact.email.clone(),
+                phone: self.contact.phone.clone(),
+                url: self.contact.websites.first().cloned(),
+                summary: self.summary.clone(),
+                location: location.map(|address| Location { address: Some(address), ..Default::default() }),
+                profiles: profiles
+                    .into_iter()
+                    .chain(self.contact.websites.iter().skip(1).map(|url| SocialProfile {
+                        network: "Website".into(),
+                        username: None,
+                        url: url.clone(),
+                    }))
+                    .collect(),
+            },
+            work: self
+                .experience
+                .iter()
+                .map(|exp| Work {
+                    name: exp.company.clone(),
+                    position: exp.title.clone(),
+                    location: exp.location.clone(),
+                    start_date: exp.start_date.clone(),
+                    end_date: exp.end_date.clone(),
+                    summary: exp.summary.clone(),
+                    highlights: exp.bullets.clone(),
+                })
+                .collect(),
+            education: self
+                .education
+                .iter()
+                .map(|edu| JsonEducation {
+                    institution: edu.institution.clone(),
+                    area: edu.field_of_study.clone(),
+                    study_type: edu.degree.clone(),
+                    start_date: edu.start_date.clone(),
+                    end_date: edu.end_date.clone(),
+                })
+                .collect(),
+            skills,
+            projects: self
+                .projects
+                .iter()
+                .map(|project| JsonProject {
+                    name: project.name.clone(),
+                    description: project.description.clone(),
+                    url: project.link.clone(),
+                    keywords: project.technologies.clone(),
+                })
+                .collect(),
+            certificates: self
+                .certifications
+                .iter()
+                .map(|cert| Certificate {
+                    name: cert.name.clone(),
+                    date: cert.date.clone(),
+                    issuer: cert.issuer.clone(),
+                    url: cert.link.clone(),
+                })
+                .collect(),
+            languages: self
+                .languages
+                .iter()
+                .map(|language| JsonLanguage { language: language.name.clone(), fluency: language.proficiency.clone() })
+                .collect(),
+        };
+        serde_json::to_value(resume).unwrap_or(Value::Null)
+    }
+}
@@ -2,7 +2,7 @@ use crate::pdf::{PdfMutationRequest, PdfMutator, StubPdfMutator};
 use crate::pipeline::{LoggingConfig, MetricSpec, PipelineConfig};
 use crate::attacks::templates::InjectionTemplate;
 use crate::{Result, RedTeamError};
-use crate::attacks::{ProfileConfig, InjectionPosition, Intensity, LowVisibilityPalette, OffpageOffset, StructuralTarget, PaddingStyle, JobAdSource, JobAdPlacement};
+pub use crate::attacks::ProfileConfig;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::path::PathBuf;
@@ -136,6 +136,8 @@ impl RedTeamEngine {
                 profiles: vec![injection.profile.clone()],
                 template: template.clone(),
                 variant_id: Some(variant_id.clone()),
+                watermark: Some("RED TEAM / TEST ONLY".into()),
+                ..Default::default()
             })?;
 
             let variant = PdfVariant {
@@ -160,6 +162,9 @@ impl RedTeamEngine {
             if impact.templates.is_empty() {
                 impact.templates = variant.templates.clone();
             }
+            if mutation.watermark_applied {
+                impact.notes.push("Variant watermarked as RED TEAM / TEST ONLY".into());
+            }
 
             impacts.push(impact);
         }
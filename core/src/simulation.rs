@@ -2,7 +2,7 @@ use crate::pdf::{PdfMutationRequest, PdfMutator, StubPdfMutator};
 use crate::pipeline::{LoggingConfig, MetricSpec, PipelineConfig};
 use crate::attacks::templates::InjectionTemplate;
 use crate::{Result, SimulationError};
-use crate::attacks::{ProfileConfig, InjectionPosition, Intensity, LowVisibilityPalette, OffpageOffset, StructuralTarget, PaddingStyle, JobAdSource, JobAdPlacement};
+pub use crate::attacks::ProfileConfig;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::path::PathBuf;
@@ -136,6 +136,7 @@ impl SimulationEngine {
                 profiles: vec![injection.profile.clone()],
                 template: template.clone(),
                 variant_id: Some(variant_id.clone()),
+                ..Default::default()
             })?;
 
             let variant = PdfVariant {